@@ -1,110 +1,292 @@
 use std::collections::HashMap;
 
-use scanner::{data::TokenType, utils::fake_cursor};
+use scanner::{
+    data::{Cursor, TokenType},
+    utils::fake_cursor,
+};
 
 use crate::{
-    parse::structures::{SymbolData, SymbolReference},
-    structures::{AnalyzeError, DeclKind, ExpKind, ExpType, Node, NodeValue, StmtKind, TreeNode},
+    config::{Config, LintLevel},
+    parse::{
+        structures::{NodeId, SymbolData, SymbolReference},
+        visit::{walk_program, BlockContext, Context, Edge, Visitor, Walk},
+    },
+    structures::{
+        AnalyzeError, DeclKind, ExpKind, ExpType, Node, NodeValue, Program, StmtKind, TreeNode,
+    },
 };
 
+// Nombre del lint que avisa cuando una declaración oculta a la de un bloque de afuera
+pub const SHADOWING: &str = "shadowing";
+
+// Tipos de los parámetros y del valor de regreso de una función
+struct Signature {
+    params: Vec<ExpType>,
+    return_type: ExpType,
+}
+
 pub struct Analyzer {
     pub errors: Vec<AnalyzeError>,
-    pub symbol_table: HashMap<String, SymbolData>
+    pub warnings: Vec<AnalyzeError>,
+    pub symbol_table: HashMap<String, SymbolData>,
+    // variables visibles en cada bloque abierto, de nombre a llave de la tabla de símbolos
+    scopes: Vec<HashMap<String, String>>,
+    // llave del símbolo al que se refiere cada identificador, asignación y stdin
+    resolved: HashMap<NodeId, String>,
+    functions: HashMap<String, Signature>,
+    // función que se está recorriendo (None en el main) y si ya tuvo algún return
+    function: Option<(String, NodeId)>,
+    has_return: bool,
+    // función a la que pertenece cada return
+    returns: HashMap<NodeId, String>,
+    location: i32,
+    shadowing: LintLevel,
+}
+
+// Nombre de la variable de una llave de la tabla. La primera declaración de un nombre usa el
+// nombre como llave y las de otros bloques agregan `#n`
+pub fn symbol_name(key: &str) -> &str {
+    key.split('#').next().unwrap_or(key)
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Analyzer {
     pub fn new() -> Self {
-        Analyzer { errors: vec![], symbol_table: HashMap::new()}
+        Analyzer {
+            errors: vec![],
+            warnings: vec![],
+            symbol_table: HashMap::new(),
+            scopes: vec![],
+            resolved: HashMap::new(),
+            functions: HashMap::new(),
+            function: None,
+            has_return: false,
+            returns: HashMap::new(),
+            location: 0,
+            shadowing: LintLevel::Warn,
+        }
     }
-        
-    fn create_symbol_table(&mut self,node: &TreeNode) -> () {
-        let mut location = 0;
-        node.pre_order_traversal(&mut |node: &Node| {
-            if let Node::Exp {
-                kind, cursor, ..
-            } = node
+
+    // Toma de la configuración el nivel de los lints
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.shadowing = config.lint_level(SHADOWING);
+        self
+    }
+
+    fn create_symbol_table(&mut self, node: &Program) {
+        // las firmas se registran primero, así una función puede llamarse a sí misma o a otra
+        // declarada después
+        for function in node.functions.iter() {
+            if let Node::Decl {
+                kind:
+                    DeclKind::Function {
+                        name,
+                        params,
+                        return_type,
+                        ..
+                    },
+                cursor,
+                ..
+            } = &function.node
             {
-                if let crate::structures::ExpKind::Id { name } = kind {
-                    match self.symbol_table.get_mut(name) {
-                        Some(data) => (*data).usages.push(SymbolReference {
-                            cursor: cursor.clone().unwrap(),
-                        }),
-                        None => {
-                            self.errors.push(AnalyzeError {
-                                message: "Uso antes de declaración".to_string(),
-                                cursor: cursor.clone().unwrap(),
-                            });
-                        },
-                    };
+                if self.functions.contains_key(name) {
+                    self.errors.push(AnalyzeError {
+                        message: format!("Doble declaración de la función {}", name),
+                        cursor: cursor.clone().unwrap_or(fake_cursor()),
+                    });
+                    continue;
                 }
+                let params = params
+                    .iter()
+                    .filter_map(|param| match &param.node {
+                        Node::Decl {
+                            kind: DeclKind::Var { typ, .. },
+                            ..
+                        } => Some(typ.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                self.functions.insert(
+                    name.clone(),
+                    Signature {
+                        params,
+                        return_type: return_type.clone(),
+                    },
+                );
             }
+        }
+        walk_program(self, node);
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+    }
+
+    // `size` es el número de elementos de un arreglo, que ocupa esas localidades seguidas
+    fn declare(&mut self, typ: &ExpType, name: &str, size: Option<usize>, cursor: Cursor) {
+        let scope = self.scopes.len() - 1;
+        if self.scopes[scope].contains_key(name) {
+            self.errors.push(AnalyzeError {
+                message: "Doble declaración".to_string(),
+                cursor,
+            });
+            return;
+        }
+        if let Some(outer) = self.lookup(name) {
+            let declaration = &self.symbol_table[&outer].declaration;
+            let shadowed = AnalyzeError {
+                message: format!(
+                    "La variable {} oculta a la declarada en ({}, {})",
+                    name, declaration.lin, declaration.col
+                ),
+                cursor: cursor.clone(),
+            };
+            match self.shadowing {
+                LintLevel::Allow => {}
+                LintLevel::Warn => self.warnings.push(shadowed),
+                LintLevel::Deny => self.errors.push(shadowed),
+            }
+        }
+        let key = (0..)
+            .map(|n| {
+                if n == 0 {
+                    name.to_string()
+                } else {
+                    format!("{}#{}", name, n)
+                }
+            })
+            .find(|key| !self.symbol_table.contains_key(key))
+            .unwrap();
+        self.symbol_table.insert(
+            key.clone(),
+            SymbolData {
+                mem_location: self.location,
+                typ: typ.clone(),
+                declaration: cursor.clone(),
+                value: None,
+                usages: vec![SymbolReference { cursor }],
+                scope,
+                function: self.function.as_ref().map(|(name, _)| name.clone()),
+                size,
+            },
+        );
+        self.location += size.unwrap_or(1) as i32;
+        self.scopes[scope].insert(name.to_string(), key);
+    }
+
+    // El cuerpo de una función comparte el bloque de sus parámetros
+    fn is_function_body(&self, ctx: &BlockContext) -> bool {
+        ctx.edge == Edge::Body
+            && ctx.owner.is_some()
+            && ctx.owner == self.function.as_ref().map(|(_, id)| *id)
+    }
 
-            if let Node::Decl { kind, cursor, .. } = node {
-                if let DeclKind::Var { typ, name, .. } = kind {
-                    if self.symbol_table.contains_key(name) {
+    fn use_symbol(&mut self, name: &str, ctx: &Context) {
+        let cursor = ctx.cursor.clone().unwrap_or(fake_cursor());
+        match self.lookup(name) {
+            Some(key) => {
+                if let Some(symbol) = self.symbol_table.get_mut(&key) {
+                    symbol.usages.push(SymbolReference { cursor });
+                }
+                self.resolved.insert(ctx.id, key);
+            }
+            None => {
+                self.errors.push(AnalyzeError {
+                    message: "Uso antes de declaración".to_string(),
+                    cursor,
+                });
+            }
+        }
+    }
+
+    // Un arreglo solo se usa con índice entero y una variable nunca lleva índice
+    fn check_array_use(
+        &mut self,
+        id: &NodeId,
+        name: &str,
+        index: Option<&TreeNode>,
+        cursor: &Option<Cursor>,
+    ) {
+        let Some(size) = self
+            .resolved
+            .get(id)
+            .and_then(|key| self.symbol_table.get(key))
+            .map(|symbol| symbol.size)
+        else {
+            return;
+        };
+        let cursor = cursor.clone().unwrap_or(fake_cursor());
+        match (size, index) {
+            (None, Some(_)) => self.errors.push(AnalyzeError {
+                message: format!("La variable {} no es un arreglo", name),
+                cursor,
+            }),
+            (Some(_), None) => self.errors.push(AnalyzeError {
+                message: format!("El arreglo {} necesita un índice", name),
+                cursor,
+            }),
+            (Some(_), Some(index)) => {
+                if let Node::Exp {
+                    typ,
+                    cursor: index_cursor,
+                    ..
+                } = &index.node
+                {
+                    if !matches!(typ, ExpType::Integer) {
                         self.errors.push(AnalyzeError {
-                            message: "Doble declaración".to_string(),
-                            cursor: cursor.clone().unwrap(),
+                            message: format!(
+                                "El índice de {} debe ser entero y es de tipo {:?}",
+                                name, typ
+                            ),
+                            cursor: index_cursor.clone().unwrap_or(cursor),
                         });
-                    } else {
-                        let mut references = vec![];
-                        references.push(SymbolReference {
-                           cursor: cursor.clone().unwrap(),
-                        });
-                        self.symbol_table.insert(
-                            name.to_owned(),
-                            SymbolData {
-                                mem_location: location,
-                                typ: typ.clone(),
-                                declaration: cursor.clone().unwrap(),
-                                value: None,
-                                usages: references,
-                            },
-                        );
-                        location += 1;
                     }
                 }
             }
+            (None, None) => {}
+        }
+    }
 
-            if let Node::Stmt { kind, cursor, .. } = node {
-                if let StmtKind::Assign { name, .. } = kind {
-                    match self.symbol_table.get_mut(name) {
-                        Some(data) => {
-                            (*data).usages.push(SymbolReference {
-                                cursor: cursor.clone().unwrap(),
-                            });
-                        }
-                        None => {
-                            self.errors.push(AnalyzeError {
-                                message: "Uso antes de declaración".to_string(),
-                                cursor: cursor.clone().unwrap(),
-                            });
-                        },
-                    };
-                }
-                if let StmtKind::In { name, .. } = kind {
-                    match self.symbol_table.get_mut(name) {
-                        Some(data) => (*data).usages.push(SymbolReference {
-                            cursor: cursor.clone().unwrap(),
-                        }),
-                        None => {
-                            self.errors.push(AnalyzeError {
-                                message: "Uso antes de declaración".to_string(),
-                                cursor: cursor.clone().unwrap(),
-                            });
-                        },
-                    };
-                }
+    // Con un índice constante se puede saber desde el análisis si se sale del arreglo
+    fn check_bounds(&mut self, id: &NodeId, name: &str, index: &TreeNode) {
+        let Some(size) = self
+            .resolved
+            .get(id)
+            .and_then(|key| self.symbol_table.get(key))
+            .and_then(|symbol| symbol.size)
+        else {
+            return;
+        };
+        if !is_constant(index) {
+            return;
+        }
+        if let Some(NodeValue::Int(position)) =
+            get_expression_value(index, &self.symbol_table, &self.resolved)
+        {
+            if position < 0 || position as usize >= size {
+                self.errors.push(AnalyzeError {
+                    message: format!(
+                        "El índice {} está fuera del arreglo {} de tamaño {}",
+                        position, name, size
+                    ),
+                    cursor: index.node.cursor().cloned().unwrap_or(fake_cursor()),
+                });
             }
-        });
-
+        }
     }
 
-    fn check_types(&mut self,node: &mut TreeNode
-    ) -> () {
-
+    fn check_types(&mut self, node: &mut Program) {
         node.post_order_traversal_mut(&mut |node: &mut Node| match node {
-            Node::Stmt { kind, ..} => {
+            Node::Stmt { kind, id, cursor, ..} => {
                 match kind {
                     StmtKind::If { condition, .. } => {
                         if let Node::Exp { typ, cursor: condition_cursor, .. } =  &condition.node {
@@ -125,39 +307,60 @@ impl Analyzer {
                             self.errors.push(AnalyzeError { message: "Condicion de Do no es booleana".to_string(), cursor: condition_cursor.clone().unwrap_or(fake_cursor()) });
                         }
                     },
-                    StmtKind::Assign { value, name, .. } => 
+                    StmtKind::Assign { name, value, index, .. } => {
+                    self.check_array_use(id, name, index.as_deref(), cursor);
                     if let Node::Exp { typ, cursor: value_cursor, .. } =  &value.node {
-                        if let Some(symbol) = self.symbol_table.get(name) {
+                        if let Some(symbol) = self.resolved.get(id).and_then(|key| self.symbol_table.get(key)) {
 
-                            if matches!(typ, ExpType::Boolean) {
-                                self.errors.push(AnalyzeError { message: "No se puede asignar una expresion booleana a una variable".to_string(), cursor: value_cursor.clone().unwrap_or(fake_cursor()) });
+                            if matches!(typ, ExpType::Boolean) && !matches!(symbol.typ, ExpType::Boolean) {
+                                self.errors.push(AnalyzeError { message: "No se puede asignar una expresion booleana a una variable numérica".to_string(), cursor: value_cursor.clone().unwrap_or(fake_cursor()) });
                             } else {
-                               
+
                                 if &symbol.typ != typ {
                                     if let (ExpType::Float, ExpType::Integer) = (&symbol.typ, typ) {
-                                        
+
                                     } else {
                                         self.errors.push(AnalyzeError { message: format!("Se asignó un tipo {:?} a una variable de tipo {:?}",typ,&symbol.typ ).to_string(), cursor: value_cursor.clone().unwrap_or(fake_cursor()) });
                                     }
                                 }
                             }
                         }
-                        
+
                     }
-                    ,
-                    StmtKind::In {.. } => {},
+                    },
+                    StmtKind::In { name } => self.check_array_use(id, name, None, cursor),
                     StmtKind::Out {expression } => {
                         if let Node::Exp { typ, cursor: exp_cursor, ..} = &expression.node  {
-                            if !matches!(typ, ExpType::Integer | ExpType::Float) {
-                                self.errors.push(AnalyzeError { message: "La sentencia out solo está definida para expresiones Enteras y Flotantes!".to_string(), cursor:  exp_cursor.clone().unwrap_or(fake_cursor()) });
+                            if !matches!(typ, ExpType::Integer | ExpType::Float | ExpType::Boolean) {
+                                self.errors.push(AnalyzeError { message: "La sentencia out solo está definida para expresiones Enteras, Flotantes y Booleanas!".to_string(), cursor:  exp_cursor.clone().unwrap_or(fake_cursor()) });
                             }
                         }
                     }
+                    StmtKind::Return { value } => {
+                        if let Some(signature) = self.returns.get(id).and_then(|name| self.functions.get(name)) {
+                            let message = match (value, &signature.return_type) {
+                                (None, ExpType::Void) => None,
+                                (None, expected) => Some(format!("La función debe regresar un valor de tipo {:?}", expected)),
+                                (Some(_), ExpType::Void) => Some("Una función void no puede regresar un valor".to_string()),
+                                (Some(value), expected) => match &value.node {
+                                    Node::Exp { typ, .. } if typ != expected && !matches!((expected, typ), (ExpType::Float, ExpType::Integer)) => {
+                                        Some(format!("Se regresó un tipo {:?} en una función de tipo {:?}", typ, expected))
+                                    },
+                                    _ => None,
+                                },
+                            };
+                            if let Some(message) = message {
+                                self.errors.push(AnalyzeError { message, cursor: cursor.clone().unwrap_or(fake_cursor()) });
+                            }
+                        }
+                    }
+                    // la llamada se revisa como expresión
+                    StmtKind::Call { .. } => {},
                 }
 
             },
             Node::Exp {
-                kind, typ, cursor, ..
+                kind, typ, cursor, id, ..
             } => match kind {
                 ExpKind::Op { op, left, right } => {
                 match right {
@@ -183,7 +386,7 @@ impl Analyzer {
                                                 message: "Una operacion arimetica no puede ser aplicada en un tipo distinto a float o entero"
                                                     .to_string(),
                                                 cursor: cursor.clone().unwrap_or(fake_cursor()),
-                                                
+
                                             });
                                         }
                                         if matches!(l_type, ExpType::Float) || matches!(r_type, ExpType::Float) {
@@ -198,7 +401,7 @@ impl Analyzer {
                                 | TokenType::LE
                                 | TokenType::GT
                                 | TokenType::GE
-                                => 
+                                =>
                                 {
                                     if let (
                                         Node::Exp { typ: l_type, .. },
@@ -259,9 +462,12 @@ impl Analyzer {
                                         *typ = ExpType::Boolean;
                                     }
                                 },
-                                _ => panic!("Operacion incorrecta. Bug en el analizador sintactico"),
+                                _ => self.errors.push(AnalyzeError {
+                                    message: format!("Operador binario no válido: {:?}", op),
+                                    cursor: cursor.clone().unwrap_or(fake_cursor()),
+                                }),
                             };
-        
+
 
                         },
                         None => {
@@ -285,71 +491,111 @@ impl Analyzer {
                         },
                     }
 
-                
-                
+
+
                 }
                 ExpKind::Const { .. } => *typ = ExpType::Integer,
                 ExpKind::ConstF { .. } => *typ = ExpType::Float,
+                ExpKind::ConstB { .. } => *typ = ExpType::Boolean,
                 ExpKind::Id { name } => {
-                    if let Some(symbol) = self.symbol_table.get(name) {
+                    if let Some(symbol) = self.resolved.get(id).and_then(|key| self.symbol_table.get(key)) {
+                        *typ = symbol.typ.clone();
+                    }
+                    self.check_array_use(id, name, None, cursor);
+                }
+                ExpKind::Index { name, index } => {
+                    if let Some(symbol) = self.resolved.get(id).and_then(|key| self.symbol_table.get(key)) {
                         *typ = symbol.typ.clone();
                     }
+                    self.check_array_use(id, name, Some(&**index), cursor);
+                }
+                ExpKind::Call { name, args } => {
+                    match self.functions.get(name) {
+                        Some(signature) => {
+                            if signature.params.len() != args.len() {
+                                self.errors.push(AnalyzeError {
+                                    message: format!("La función {} espera {} argumentos y recibió {}", name, signature.params.len(), args.len()),
+                                    cursor: cursor.clone().unwrap_or(fake_cursor()),
+                                });
+                            } else {
+                                for (index, (param, arg)) in signature.params.iter().zip(args.iter()).enumerate() {
+                                    if let Node::Exp { typ: arg_type, cursor: arg_cursor, .. } = &arg.node {
+                                        if param != arg_type && !matches!((param, arg_type), (ExpType::Float, ExpType::Integer)) {
+                                            self.errors.push(AnalyzeError {
+                                                message: format!("El argumento {} de {} debe ser de tipo {:?} y es de tipo {:?}", index + 1, name, param, arg_type),
+                                                cursor: arg_cursor.clone().or(cursor.clone()).unwrap_or(fake_cursor()),
+                                            });
+                                        }
+                                    }
+                                }
+                            }
+                            *typ = signature.return_type.clone();
+                        },
+                        None => self.errors.push(AnalyzeError {
+                            message: format!("La función {} no está declarada", name),
+                            cursor: cursor.clone().unwrap_or(fake_cursor()),
+                        }),
+                    }
                 }
             },
             Node::Decl { .. } => {},
         });
-
     }
 
-    fn evaluate_expressions(
-        &mut self,
-        node: &mut TreeNode,
-    ) -> (){
+    fn evaluate_expressions(&mut self, node: &mut Program) {
         node.post_order_traversal_mut(&mut |node: &mut Node| {
             // Verificamos si es un nodo de expresión
             match node {
-                Node::Stmt { kind, .. } => {
-                    if let StmtKind::Assign { name, value, exp_value } = kind {
-                        // aqui aprovechamos que tenemos el valor calculado (gracias a el recorrido postorden, por lo que guardamos este valor en la tabla de simbolos)
-                        match &value.node {
-                            Node::Exp { val, typ, cursor: exp_cursor, .. } => {
-                                if let Some(symbol) = self.symbol_table.get_mut(name) {
-                                    if let (ExpType::Integer, ExpType::Float) = (&symbol.typ, typ) {
-                                        self.errors.push(
-                                            AnalyzeError { message: 
-                                                "La asignación de un flotante a un entero no es válida".to_string(), cursor: exp_cursor.clone().unwrap_or(fake_cursor()) }
-                                        );
+                Node::Stmt { kind: StmtKind::Assign { name, value, exp_value, index }, id, cursor } => {
+                    if let Some(index) = index {
+                        self.check_bounds(id, name, index);
+                    }
+                    // aqui aprovechamos que tenemos el valor calculado (gracias a el recorrido postorden, por lo que guardamos este valor en la tabla de simbolos)
+                    match &value.node {
+                        Node::Exp { val, typ, cursor: exp_cursor, .. } => {
+                            if let Some(symbol) = self.resolved.get(id).and_then(|key| self.symbol_table.get_mut(key)) {
+                                if let (ExpType::Integer, ExpType::Float) = (&symbol.typ, typ) {
+                                    self.errors.push(
+                                        AnalyzeError { message:
+                                            "La asignación de un flotante a un entero no es válida".to_string(), cursor: exp_cursor.clone().unwrap_or(fake_cursor()) }
+                                    );
+                                } else if matches!(symbol.typ, ExpType::Boolean) != matches!(typ, ExpType::Boolean) {
+                                    // el error ya se reportó al revisar los tipos; el valor no se guarda
+                                } else if index.is_some() || symbol.size.is_some() {
+                                    // los elementos de los arreglos no guardan su valor en la tabla
+                                } else {
+                                    if let ExpType::Float = &symbol.typ{
+                                        symbol.value = val.clone().and_then(|v| v.to_float());
+                                        *exp_value = val.clone().and_then(|v| v.to_float());
                                     } else {
-                                        if let ExpType::Float = &symbol.typ{
-                                            symbol.value = val.clone().map(|v| v.to_float()).flatten();
-                                            *exp_value = val.clone().map(|v| v.to_float()).flatten();
-                                        } else {
-                                            symbol.value = val.clone();
-                                            *exp_value = val.clone();
-                                        }
-                                       
+                                        symbol.value = val.clone();
+                                        *exp_value = val.clone();
                                     }
-                                
+
                                 }
+
                             }
-                            _ => panic!("Error en el analizador sintáctico. El lado derecho de una asignación no fue expresión."),
                         }
+                        _ => self.errors.push(AnalyzeError {
+                            message: "El lado derecho de una asignación no es una expresión".to_string(),
+                            cursor: cursor.clone().unwrap_or(fake_cursor()),
+                        }),
                     }
                 }
                 Node::Exp {
-                    kind, cursor, val, ..
+                    kind, cursor, val, id, ..
                 } => match kind {
                     // Evaluamos expresiones con operadores
                     ExpKind::Op { op, left, right } => {
                         // Evaluamos las subexpresiones (si hay valor)
-                    
-                        if let Some(left_val) = 
-                            get_expression_value(left, &self.symbol_table)
+
+                        if let Some(left_val) =
+                            get_expression_value(left, &self.symbol_table, &self.resolved)
                         {
 
                             match  right
                             .as_ref()
-                            .and_then(|r| get_expression_value(r, &self.symbol_table)) {
+                            .and_then(|r| get_expression_value(r, &self.symbol_table, &self.resolved)) {
                                 Some(right_val) => {
                                     // if matches!(typ, ExpType::Float) {
                                         //     left_val  = left_val.to_float();
@@ -358,66 +604,66 @@ impl Analyzer {
                                     let result = match op {
                                         TokenType::SUM => {
                                             let op = left_val + right_val;
-                                            if let None = &op {
-                                                self.errors.push(AnalyzeError { message: 
+                                            if op.is_none() {
+                                                self.errors.push(AnalyzeError { message:
                                                     "La suma solo está definida para numeros".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                             }
                                             op
                                         },
                                         TokenType::MIN => {
                                             let op = left_val - right_val;
-                                            if let None = &op {
-                                                self.errors.push(AnalyzeError { message: 
+                                            if op.is_none() {
+                                                self.errors.push(AnalyzeError { message:
                                                     "La resta solo está definida para numeros".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                             }
                                             op
                                         },
                                         TokenType::TIMES => {
                                                 let op = left_val * right_val;
-                                                if let None = &op {
-                                                    self.errors.push(AnalyzeError { message: 
+                                                if op.is_none() {
+                                                    self.errors.push(AnalyzeError { message:
                                                         "La multiplicación solo está definida para numeros".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                                 }
                                                 op
                                         },
                                         TokenType::MODULUS => {
                                             let op = left_val % right_val;
-                                            if let None = &op {
-                                                self.errors.push(AnalyzeError { message: 
+                                            if op.is_none() {
+                                                self.errors.push(AnalyzeError { message:
                                                     "El módulo solo está definida para numeros".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                             }
                                             op
                                         },
                                         TokenType::POWER => {
                                             let op = left_val.pow(right_val);
-                                            if let None = &op {
-                                                self.errors.push(AnalyzeError { message: 
+                                            if op.is_none() {
+                                                self.errors.push(AnalyzeError { message:
                                                     "La exponenciación solo está definida para numeros".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                             }
                                             op
-                                        },                                     
+                                        },
                                         TokenType::DIV => {
                                             {
                                                 match right_val {
-                                                    NodeValue::Int(rv) if rv == 0 => {
+                                                    NodeValue::Int(0) => {
                                                         self.errors.push(AnalyzeError {
                                                             message: "División entre cero".to_string(),
-                                                            cursor: cursor.clone().unwrap(),
+                                                            cursor: cursor.clone().unwrap_or(fake_cursor()),
                                                         });
                                                         return;
                                                     }
-                                                    NodeValue::Float(rv) if rv == 0.0 => {
+                                                    NodeValue::Float(0.0) => {
                                                         self.errors.push(AnalyzeError {
                                                             message: "División entre cero".to_string(),
-                                                            cursor: cursor.clone().unwrap(),
+                                                            cursor: cursor.clone().unwrap_or(fake_cursor()),
                                                         });
                                                         return;
                                                     }
                                                     _ => {}
                                                 }
                                                 let op = left_val / right_val;
-                                                if let None = &op {
-                                                    self.errors.push(AnalyzeError { message: 
+                                                if op.is_none() {
+                                                    self.errors.push(AnalyzeError { message:
                                                         "La división solo está definida para numeros".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                                 }
                                                 op
@@ -445,7 +691,7 @@ impl Analyzer {
                                             if let (NodeValue::Boolean(l), NodeValue::Boolean(r)) = (left_val, right_val) {
                                                 Some(NodeValue::Boolean(l && r))
                                             } else {
-                                                self.errors.push(AnalyzeError { message: 
+                                                self.errors.push(AnalyzeError { message:
                                                     "El operador AND solo está definida para booleanos".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                                 None
                                             }
@@ -454,14 +700,15 @@ impl Analyzer {
                                             if let (NodeValue::Boolean(l), NodeValue::Boolean(r)) = (left_val, right_val) {
                                                Some( NodeValue::Boolean(l || r))
                                             } else {
-                                                self.errors.push(AnalyzeError { message: 
+                                                self.errors.push(AnalyzeError { message:
                                                     "El operador OR solo está definida para booleanos".to_string(), cursor:  cursor.clone().unwrap_or(fake_cursor()) });
                                                 None
                                             }
                                         }
                                         _ => {
-                                            // no deberia pasar
-                                            panic!("Se asignó un token no valido a la operacion")
+                                            self.errors.push(AnalyzeError { message:
+                                                format!("Operador binario no válido: {:?}", op), cursor: cursor.clone().unwrap_or(fake_cursor()) });
+                                            None
                                         }
                                     };
                                     *val = result;
@@ -473,49 +720,60 @@ impl Analyzer {
                                         } else {
                                             // errors.push("value");
                                         }
-                                    } else {
-
                                     }
                                 }, // NEGACION (unica operacion binaria)
                             }
-                        
-                        
+
+
                         } else { // aquí, al no tener algun valor en izq o derecha, se da valor ausente
                             *val = None;
                         }
                     }
                     ExpKind::Id { name } => {
                         // Si es un identificador, comprobamos si está declarado
-                        if let Some(symbol) = self.symbol_table.get(name) {
+                        if let Some(symbol) = self.resolved.get(id).and_then(|key| self.symbol_table.get(key)) {
                             *val = symbol.value.clone(); // obtenemos el valor de la tabla de símbolos :)
                         } else {
                             self.errors.push(AnalyzeError {
                                 message: format!("No se puede evaluar el valor de una variable no declarada: {}", name),
-                                cursor: cursor.clone().unwrap(),
+                                cursor: cursor.clone().unwrap_or(fake_cursor()),
                             });
                         }
 
                     },
-                    ExpKind::Const { value } => *val = Some(NodeValue::Int(value.clone())),
-                    ExpKind::ConstF { value, } => *val = Some(NodeValue::Float(value.clone())),
+                    ExpKind::Const { value } => *val = Some(NodeValue::Int(*value)),
+                    ExpKind::ConstF { value, } => *val = Some(NodeValue::Float(*value)),
+                    ExpKind::ConstB { value } => *val = Some(NodeValue::Boolean(*value)),
+                    ExpKind::Index { name, index } => self.check_bounds(id, name, index),
                     _ => {}
                 },
                 _ => {}
             }
         });
+    }
 
+    pub fn analyze(self, node: &mut Program) -> (Vec<AnalyzeError>, HashMap<String, SymbolData>) {
+        let (errors, _, symbol_table) = self.analyze_with_warnings(node);
+        (errors, symbol_table)
     }
 
-    pub fn analyze(mut self, node: &mut TreeNode) -> (Vec<AnalyzeError>,HashMap<String, SymbolData>) {
+    // Igual que `analyze`, pero también regresa las advertencias de los lints
+    pub fn analyze_with_warnings(
+        mut self,
+        node: &mut Program,
+    ) -> (
+        Vec<AnalyzeError>,
+        Vec<AnalyzeError>,
+        HashMap<String, SymbolData>,
+    ) {
         self.create_symbol_table(node);
         self.check_types(node);
         self.evaluate_expressions(node);
-        (self.errors, self.symbol_table)
+        (self.errors, self.warnings, self.symbol_table)
     }
 }
 
-
-pub fn analyze(node: &mut TreeNode) -> (Vec<AnalyzeError>,HashMap<String, SymbolData>) {
+pub fn analyze(node: &mut Program) -> (Vec<AnalyzeError>, HashMap<String, SymbolData>) {
     let analyzer = Analyzer::new();
     analyzer.analyze(node)
 }
@@ -523,29 +781,161 @@ pub fn analyze(node: &mut TreeNode) -> (Vec<AnalyzeError>,HashMap<String, Symbol
 fn get_expression_value(
     node: &TreeNode,
     symbol_table: &HashMap<String, SymbolData>,
+    resolved: &HashMap<NodeId, String>,
 ) -> Option<NodeValue> {
-    if let Node::Exp { kind, val, .. } = &node.node {
+    if let Node::Exp { kind, val, id, .. } = &node.node {
         match kind {
             ExpKind::Const { value } => Some(NodeValue::Int(*value)), // Constantes enteras
             ExpKind::ConstF { value } => Some(NodeValue::Float(*value)), // Constantes enteras
-            ExpKind::Id { name } => {
+            ExpKind::ConstB { value } => Some(NodeValue::Boolean(*value)), // Constantes booleanas
+            ExpKind::Id { .. } => {
                 // Si es un identificador, buscamos su valor en la tabla de símbolos
-                symbol_table.get(name).map(|data| data.value.clone())? // Devuelve el valor asignado a la variable como valor
+                resolved
+                    .get(id)
+                    .and_then(|key| symbol_table.get(key))
+                    .map(|data| data.value.clone())? // Devuelve el valor asignado a la variable como valor
             }
-            ExpKind::Op { .. } => val.clone(),
+            ExpKind::Op { .. } | ExpKind::Call { .. } | ExpKind::Index { .. } => val.clone(),
         }
     } else {
         None
     }
 }
 
+// La expresión no usa variables ni llamadas, así que su valor no cambia al ejecutar
+fn is_constant(node: &TreeNode) -> bool {
+    let mut constant = true;
+    node.pre_order_traversal(&mut |node| {
+        if let Node::Exp {
+            kind: ExpKind::Id { .. } | ExpKind::Call { .. } | ExpKind::Index { .. },
+            ..
+        } = node
+        {
+            constant = false;
+        }
+    });
+    constant
+}
 
 pub fn debug(node: &TreeNode) {
     node.pre_order_traversal(&mut |node| {
-        if let Node::Exp { kind, .. } = node {
-            if let ExpKind::Id { name } = kind {
-                println!("{:?}\n-------", node)
-            }
+        if let Node::Exp {
+            kind: ExpKind::Id { .. },
+            ..
+        } = node
+        {
+            println!("{:?}\n-------", node)
         }
     });
 }
+
+// Abre un bloque por cada cuerpo de if, while y do; lo que se declara en él deja de ser visible al
+// cerrarlo. Cada función abre su propio bloque con sus parámetros y no ve las variables del main
+impl Visitor for Analyzer {
+    fn enter_block(&mut self, _block: &[TreeNode], ctx: &BlockContext) -> Walk {
+        if !self.is_function_body(ctx) {
+            self.scopes.push(HashMap::new());
+        }
+        Walk::Continue
+    }
+
+    fn exit_block(&mut self, _block: &[TreeNode], ctx: &BlockContext) {
+        if !self.is_function_body(ctx) {
+            self.scopes.pop();
+        }
+    }
+
+    fn visit_function(
+        &mut self,
+        name: &str,
+        _params: &[TreeNode],
+        _return_type: &ExpType,
+        _body: &[TreeNode],
+        ctx: &Context,
+    ) -> Walk {
+        self.scopes.push(HashMap::new());
+        self.function = Some((name.to_string(), ctx.id));
+        self.has_return = false;
+        Walk::Continue
+    }
+
+    fn exit_node(&mut self, node: &TreeNode, ctx: &Context) {
+        if let Node::Decl {
+            kind: DeclKind::Function {
+                name, return_type, ..
+            },
+            ..
+        } = &node.node
+        {
+            if !self.has_return && !matches!(return_type, ExpType::Void) {
+                self.errors.push(AnalyzeError {
+                    message: format!("La función {} no regresa ningún valor", name),
+                    cursor: ctx.cursor.clone().unwrap_or(fake_cursor()),
+                });
+            }
+            self.scopes.pop();
+            self.function = None;
+        }
+    }
+
+    fn visit_return(&mut self, _value: Option<&TreeNode>, ctx: &Context) -> Walk {
+        match &self.function {
+            Some((name, _)) => {
+                self.returns.insert(ctx.id, name.clone());
+                self.has_return = true;
+            }
+            None => self.errors.push(AnalyzeError {
+                message: "Solo se puede usar return dentro de una función".to_string(),
+                cursor: ctx.cursor.clone().unwrap_or(fake_cursor()),
+            }),
+        }
+        Walk::Continue
+    }
+
+    fn visit_decl(&mut self, typ: &ExpType, name: &str, ctx: &Context) -> Walk {
+        self.declare(typ, name, None, ctx.cursor.clone().unwrap_or(fake_cursor()));
+        Walk::Continue
+    }
+
+    fn visit_array(&mut self, typ: &ExpType, name: &str, size: usize, ctx: &Context) -> Walk {
+        self.declare(
+            typ,
+            name,
+            Some(size),
+            ctx.cursor.clone().unwrap_or(fake_cursor()),
+        );
+        Walk::Continue
+    }
+
+    fn visit_assign(
+        &mut self,
+        name: &str,
+        _index: Option<&TreeNode>,
+        _value: &TreeNode,
+        ctx: &Context,
+    ) -> Walk {
+        self.use_symbol(name, ctx);
+        Walk::Continue
+    }
+
+    fn visit_in(&mut self, name: &str, ctx: &Context) -> Walk {
+        self.use_symbol(name, ctx);
+        Walk::Continue
+    }
+
+    fn visit_id(&mut self, name: &str, _typ: &ExpType, ctx: &Context) -> Walk {
+        self.use_symbol(name, ctx);
+        Walk::Continue
+    }
+
+    fn visit_index(
+        &mut self,
+        name: &str,
+        _index: &TreeNode,
+        _typ: &ExpType,
+        ctx: &Context,
+    ) -> Walk {
+        self.use_symbol(name, ctx);
+        Walk::Continue
+    }
+}
@@ -0,0 +1,408 @@
+// Servidor LSP para Vanilla (JSON-RPC por stdio). Cada cambio del documento se vuelve a
+// tokenizar, parsear y analizar, y se publican los diagnósticos
+
+use std::{collections::HashMap, error::Error, path::Path};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{GotoDefinition, HoverRequest, References, Request as LspRequest},
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, InitializeParams, Location, MarkupContent, MarkupKind,
+    OneOf, Position, PositionEncodingKind, PublishDiagnosticsParams, Range, ReferenceParams,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use parser::{
+    config::Config,
+    format::format_float,
+    parse,
+    source::{self, tokenize_str},
+    structures::{ExpType, NodeValue, SymbolData},
+    symbol_name, Analyzer,
+};
+use scanner::data::{Cursor, Token, TokenType};
+use serde::{de::DeserializeOwned, Serialize};
+
+// Unidades en las que se cuentan las columnas de las posiciones. Se usa la primera que ofrezca
+// el cliente; sin ofrecer ninguna, el protocolo indica UTF-16
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Utf16,
+    Utf32,
+}
+
+impl Encoding {
+    fn negotiate(params: &InitializeParams) -> Self {
+        let offered = params
+            .capabilities
+            .general
+            .as_ref()
+            .and_then(|general| general.position_encodings.as_ref());
+        offered
+            .into_iter()
+            .flatten()
+            .find_map(|kind| match kind.as_str() {
+                "utf-8" => Some(Encoding::Utf8),
+                "utf-16" => Some(Encoding::Utf16),
+                "utf-32" => Some(Encoding::Utf32),
+                _ => None,
+            })
+            .unwrap_or(Encoding::Utf16)
+    }
+
+    fn kind(&self) -> PositionEncodingKind {
+        match self {
+            Encoding::Utf8 => PositionEncodingKind::UTF8,
+            Encoding::Utf16 => PositionEncodingKind::UTF16,
+            Encoding::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    fn length(&self, text: impl Iterator<Item = char>) -> u32 {
+        text.map(|c| match self {
+            Encoding::Utf8 => c.len_utf8(),
+            Encoding::Utf16 => c.len_utf16(),
+            Encoding::Utf32 => 1,
+        })
+        .sum::<usize>() as u32
+    }
+}
+
+// Resultado del último análisis de un documento abierto
+struct Document {
+    tokens: Vec<Token>,
+    symbols: HashMap<String, SymbolData>,
+    lines: Vec<String>,
+    encoding: Encoding,
+}
+
+impl Document {
+    // Los cursores cuentan caracteres; el editor cuenta en las unidades de `encoding`
+    fn position(&self, cursor: &Cursor) -> Position {
+        let (lin, col) = source::position(cursor);
+        let character = match self.lines.get(lin) {
+            Some(line) => self.encoding.length(line.chars().take(col)),
+            None => col as u32,
+        };
+        Position::new(lin as u32, character)
+    }
+
+    fn token_range(&self, token: &Token) -> Range {
+        self.identifier_range(&token.lexemme, &token.start)
+    }
+
+    fn identifier_range(&self, name: &str, cursor: &Cursor) -> Range {
+        let start = self.position(cursor);
+        let end = Position::new(
+            start.line,
+            start.character + self.encoding.length(name.chars()),
+        );
+        Range::new(start, end)
+    }
+
+    fn cursor_range(&self, cursor: &Cursor) -> Range {
+        let start = self.position(cursor);
+        Range::new(start, Position::new(start.line, start.character + 1))
+    }
+
+    // Identificador que está bajo la posición del editor
+    fn identifier_at(&self, position: Position) -> Option<&Token> {
+        self.tokens.iter().find(|token| {
+            let range = self.token_range(token);
+            token.token_type == TokenType::ID
+                && range.start.line == position.line
+                && range.start.character <= position.character
+                && position.character <= range.end.character
+        })
+    }
+
+    // La declaración y los usos de la tabla de símbolos tienen el cursor del identificador
+    fn resolve(&self, name: &str, cursor: &Cursor) -> Range {
+        self.identifier_range(name, cursor)
+    }
+}
+
+fn analyze(text: &str, encoding: Encoding, config: &Config) -> (Document, Vec<Diagnostic>) {
+    let mut document = Document {
+        tokens: vec![],
+        symbols: HashMap::new(),
+        lines: text.lines().map(str::to_string).collect(),
+        encoding,
+    };
+    let mut diagnostics = vec![];
+    let diagnostic = |severity: DiagnosticSeverity, range: Range, message: String| Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some("vanilla".to_string()),
+        message,
+        ..Default::default()
+    };
+    let error =
+        |range: Range, message: String| diagnostic(DiagnosticSeverity::ERROR, range, message);
+
+    let (tokens, lex_errors) = tokenize_str(text);
+    for err in lex_errors.iter() {
+        let range = Range::new(document.position(&err.start), document.position(&err.end));
+        diagnostics.push(error(range, err.message.clone()));
+    }
+
+    document.tokens = tokens.clone();
+    let (root, parse_errors) = parse(tokens);
+    let end = document
+        .tokens
+        .last()
+        .map(|token| document.token_range(token))
+        .unwrap_or_default();
+    for err in parse_errors {
+        let range = match &err.current_token {
+            Some(token) => document.token_range(token),
+            None => end,
+        };
+        diagnostics.push(error(range, err.message));
+    }
+
+    if let Some(mut root) = root {
+        let (errors, warnings, symbols) = Analyzer::new()
+            .with_config(config)
+            .analyze_with_warnings(&mut root);
+        for err in errors {
+            let range = document.cursor_range(&err.cursor);
+            diagnostics.push(error(range, err.message));
+        }
+        for warning in warnings {
+            let range = document.cursor_range(&warning.cursor);
+            diagnostics.push(diagnostic(
+                DiagnosticSeverity::WARNING,
+                range,
+                warning.message,
+            ));
+        }
+        document.symbols = symbols;
+    }
+    (document, diagnostics)
+}
+
+fn type_keyword(typ: &ExpType) -> String {
+    match typ {
+        ExpType::Integer => "integer".to_string(),
+        ExpType::Float => "double".to_string(),
+        typ => format!("{:?}", typ).to_lowercase(),
+    }
+}
+
+fn value_text(value: &NodeValue) -> String {
+    match value {
+        NodeValue::Int(value) => value.to_string(),
+        NodeValue::Float(value) => format_float(*value),
+        NodeValue::Boolean(value) => value.to_string(),
+    }
+}
+
+// `vanilla.toml` más cercano al documento, como lo busca `build` desde el directorio actual. Un
+// archivo inválido se reporta en stderr y se ignora
+fn load_config(dir: &Path) -> Option<Config> {
+    let path = Config::discover(dir)?;
+    match Config::load(&path) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            eprintln!("ERROR: Invalid {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+struct Server {
+    documents: HashMap<Url, Document>,
+    encoding: Encoding,
+}
+
+impl Server {
+    fn update(&mut self, uri: Url, text: &str) -> PublishDiagnosticsParams {
+        let config = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| load_config(path.parent()?))
+            .unwrap_or_default();
+        let (document, diagnostics) = analyze(text, self.encoding, &config);
+        self.documents.insert(uri.clone(), document);
+        PublishDiagnosticsParams::new(uri, diagnostics, None)
+    }
+
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Option<PublishDiagnosticsParams> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = params(notification)?;
+                Some(self.update(params.text_document.uri, &params.text_document.text))
+            }
+            DidChangeTextDocument::METHOD => {
+                // sincronización completa: el último cambio trae todo el texto
+                let params: DidChangeTextDocumentParams = params(notification)?;
+                let change = params.content_changes.last()?;
+                Some(self.update(params.text_document.uri, &change.text))
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = params(notification)?;
+                self.documents.remove(&params.text_document.uri);
+                Some(PublishDiagnosticsParams::new(
+                    params.text_document.uri,
+                    vec![],
+                    None,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => respond(request, |params| self.hover(params)),
+            GotoDefinition::METHOD => respond(request, |params| self.definition(params)),
+            References::METHOD => respond(request, |params| self.references(params)),
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Método no soportado: {}", request.method),
+            ),
+        }
+    }
+
+    // Documento y símbolo que están bajo la posición del editor. Si el nombre se declara en
+    // varios bloques, se usa el símbolo que registró este identificador como uso; si ninguno lo
+    // registró (por ejemplo una variable no declarada) no hay símbolo
+    fn symbol_at(&self, uri: &Url, position: Position) -> Option<(&Document, &str, &SymbolData)> {
+        let document = self.documents.get(uri)?;
+        let token = document.identifier_at(position)?;
+        let range = document.token_range(token);
+        let symbol = document
+            .symbols
+            .iter()
+            .filter(|(key, _)| symbol_name(key) == token.lexemme)
+            .map(|(_, symbol)| symbol)
+            .find(|symbol| {
+                symbol
+                    .usages
+                    .iter()
+                    .any(|usage| document.resolve(&token.lexemme, &usage.cursor) == range)
+            })?;
+        Some((document, &token.lexemme, symbol))
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let (document, name, symbol) =
+            self.symbol_at(&position.text_document.uri, position.position)?;
+        let mut text = format!("```vanilla\n{} {}\n```", type_keyword(&symbol.typ), name);
+        if let Some(value) = &symbol.value {
+            text.push_str(&format!("\n\nValor: `{}`", value_text(value)));
+        }
+        text.push_str(&format!(
+            "\n\nLocalidad de memoria: {}",
+            symbol.mem_location
+        ));
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: text,
+            }),
+            range: document
+                .identifier_at(position.position)
+                .map(|token| document.token_range(token)),
+        })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let uri = position.text_document.uri;
+        let (document, name, symbol) = self.symbol_at(&uri, position.position)?;
+        let range = document.resolve(name, &symbol.declaration);
+        Some(GotoDefinitionResponse::Scalar(Location::new(uri, range)))
+    }
+
+    fn references(&self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let position = params.text_document_position;
+        let uri = position.text_document.uri;
+        let (document, name, symbol) = self.symbol_at(&uri, position.position)?;
+        let declaration = document.resolve(name, &symbol.declaration);
+        let mut locations: Vec<Location> = vec![];
+        for usage in symbol.usages.iter() {
+            let range = document.resolve(name, &usage.cursor);
+            if range == declaration && !params.context.include_declaration {
+                continue;
+            }
+            // `x++` registra dos usos en la misma posición
+            if locations.iter().all(|location| location.range != range) {
+                locations.push(Location::new(uri.clone(), range));
+            }
+        }
+        Some(locations)
+    }
+}
+
+fn params<P: DeserializeOwned>(notification: Notification) -> Option<P> {
+    serde_json::from_value(notification.params).ok()
+}
+
+fn respond<P: DeserializeOwned, R: Serialize>(
+    request: Request,
+    handler: impl FnOnce(P) -> R,
+) -> Response {
+    match serde_json::from_value(request.params) {
+        Ok(params) => Response::new_ok(request.id, handler(params)),
+        Err(e) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+    let (id, params) = connection.initialize_start()?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    let encoding = Encoding::negotiate(&params);
+    let capabilities = ServerCapabilities {
+        position_encoding: Some(encoding.kind()),
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize_finish(id, serde_json::json!({ "capabilities": capabilities }))?;
+
+    let mut server = Server {
+        documents: HashMap::new(),
+        encoding,
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(diagnostics) = server.handle_notification(notification) {
+                    connection
+                        .sender
+                        .send(Message::Notification(Notification::new(
+                            PublishDiagnostics::METHOD.to_string(),
+                            diagnostics,
+                        )))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    // el hilo de escritura termina hasta que se cierra la conexión
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
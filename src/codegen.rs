@@ -0,0 +1,401 @@
+use std::{collections::HashMap, fmt};
+
+use scanner::data::TokenType;
+
+use crate::{
+    format::{format_float, operator_lexemme},
+    structures::{DeclKind, ExpKind, Node, Program, StmtKind, TreeNode},
+};
+
+// Operando de una instrucción de tres direcciones
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Var(String),
+    Temp(usize),
+    Int(i32),
+    Float(f32),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Copy {
+        dest: Operand,
+        value: Operand,
+    },
+    Binary {
+        dest: Operand,
+        op: TokenType,
+        left: Operand,
+        right: Operand,
+    },
+    Unary {
+        dest: Operand,
+        op: TokenType,
+        value: Operand,
+    },
+    Label(usize),
+    Goto(usize),
+    IfFalse {
+        condition: Operand,
+        label: usize,
+    },
+    // `dest = array[index]` y `array[index] = value`. El índice es la posición del elemento; su
+    // localidad es la `mem_location` del arreglo más el índice
+    Load {
+        dest: Operand,
+        array: String,
+        index: Operand,
+    },
+    Store {
+        array: String,
+        index: Operand,
+        value: Operand,
+    },
+    Read(String),
+    Write(Operand),
+    // inicio de una función con el nombre de sus parámetros
+    Function {
+        name: String,
+        params: Vec<String>,
+    },
+    Param(Operand),
+    // `dest` es None cuando la llamada se usa como sentencia
+    Call {
+        dest: Option<Operand>,
+        name: String,
+        args: usize,
+    },
+    Return(Option<Operand>),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Var(name) => write!(f, "{}", name),
+            Operand::Temp(n) => write!(f, "t{}", n),
+            Operand::Int(value) => write!(f, "{}", value),
+            Operand::Float(value) => write!(f, "{}", format_float(*value)),
+            Operand::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Copy { dest, value } => write!(f, "    {} = {}", dest, value),
+            Instruction::Binary {
+                dest,
+                op,
+                left,
+                right,
+            } => write!(
+                f,
+                "    {} = {} {} {}",
+                dest,
+                left,
+                operator_lexemme(op),
+                right
+            ),
+            Instruction::Unary { dest, op, value } => {
+                write!(f, "    {} = {}{}", dest, operator_lexemme(op), value)
+            }
+            Instruction::Label(label) => write!(f, "L{}:", label),
+            Instruction::Goto(label) => write!(f, "    goto L{}", label),
+            Instruction::IfFalse { condition, label } => {
+                write!(f, "    if_false {} goto L{}", condition, label)
+            }
+            Instruction::Load { dest, array, index } => {
+                write!(f, "    {} = {}[{}]", dest, array, index)
+            }
+            Instruction::Store {
+                array,
+                index,
+                value,
+            } => write!(f, "    {}[{}] = {}", array, index, value),
+            Instruction::Read(name) => write!(f, "    read {}", name),
+            Instruction::Write(value) => write!(f, "    write {}", value),
+            Instruction::Function { name, params } => {
+                write!(f, "func {}({}):", name, params.join(", "))
+            }
+            Instruction::Param(value) => write!(f, "    param {}", value),
+            Instruction::Call {
+                dest: Some(dest),
+                name,
+                args,
+            } => write!(f, "    {} = call {}, {}", dest, name, args),
+            Instruction::Call {
+                dest: None,
+                name,
+                args,
+            } => write!(f, "    call {}, {}", name, args),
+            Instruction::Return(Some(value)) => write!(f, "    return {}", value),
+            Instruction::Return(None) => write!(f, "    return"),
+        }
+    }
+}
+
+// Genera código de tres direcciones para un árbol ya analizado
+pub fn generate_tac(program: &Program) -> Vec<Instruction> {
+    let mut generator = Generator::default();
+    generator.block(&program.body);
+    if !program.functions.is_empty() {
+        // el main termina antes del código de las funciones
+        generator.code.push(Instruction::Return(None));
+        for function in program.functions.iter() {
+            generator.function(function);
+        }
+    }
+    generator.code
+}
+
+pub fn tac_to_string(code: &[Instruction]) -> String {
+    code.iter()
+        .map(|instruction| format!("{}\n", instruction))
+        .collect()
+}
+
+#[derive(Default)]
+struct Generator {
+    code: Vec<Instruction>,
+    temps: usize,
+    labels: usize,
+    // nombre de cada variable visible en los bloques abiertos. Una declaración que oculta a otra
+    // recibe un nombre nuevo (`x.1`), que no puede chocar con un identificador
+    scopes: Vec<HashMap<String, String>>,
+    renamed: usize,
+}
+
+impl Generator {
+    fn temp(&mut self) -> Operand {
+        self.temps += 1;
+        Operand::Temp(self.temps - 1)
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels += 1;
+        self.labels - 1
+    }
+
+    fn block(&mut self, block: &[TreeNode]) {
+        self.scopes.push(HashMap::new());
+        for node in block.iter() {
+            self.statement(node);
+        }
+        self.scopes.pop();
+    }
+
+    fn function(&mut self, node: &TreeNode) {
+        let Node::Decl {
+            kind: DeclKind::Function {
+                name, params, body, ..
+            },
+            ..
+        } = &node.node
+        else {
+            return;
+        };
+        // los parámetros están en el mismo bloque que el cuerpo
+        self.scopes.push(HashMap::new());
+        let mut names = vec![];
+        for param in params.iter() {
+            if let Node::Decl {
+                kind: DeclKind::Var { name, .. },
+                ..
+            } = &param.node
+            {
+                self.declare(name);
+                names.push(self.variable(name));
+            }
+        }
+        self.code.push(Instruction::Function {
+            name: name.clone(),
+            params: names,
+        });
+        for node in body.iter() {
+            self.statement(node);
+        }
+        self.scopes.pop();
+        if !matches!(self.code.last(), Some(Instruction::Return(_))) {
+            self.code.push(Instruction::Return(None));
+        }
+    }
+
+    fn declare(&mut self, name: &str) {
+        let target = if self.scopes.iter().any(|scope| scope.contains_key(name)) {
+            self.renamed += 1;
+            format!("{}.{}", name, self.renamed)
+        } else {
+            name.to_string()
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), target);
+        }
+    }
+
+    fn variable(&self, name: &str) -> String {
+        let target = self.scopes.iter().rev().find_map(|scope| scope.get(name));
+        target.map_or(name, String::as_str).to_string()
+    }
+
+    fn statement(&mut self, node: &TreeNode) {
+        match &node.node {
+            // las declaraciones no generan código
+            Node::Decl { kind, .. } => {
+                if let DeclKind::Var { name, .. } | DeclKind::Array { name, .. } = kind {
+                    self.declare(name)
+                }
+            }
+            Node::Stmt { kind, .. } => match kind {
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } => {
+                    let condition = self.expression(condition);
+                    let else_label = self.label();
+                    self.code.push(Instruction::IfFalse {
+                        condition,
+                        label: else_label,
+                    });
+                    self.block(then_branch);
+                    match else_branch {
+                        Some(else_branch) => {
+                            let end = self.label();
+                            self.code.push(Instruction::Goto(end));
+                            self.code.push(Instruction::Label(else_label));
+                            self.block(else_branch);
+                            self.code.push(Instruction::Label(end));
+                        }
+                        None => self.code.push(Instruction::Label(else_label)),
+                    }
+                }
+                StmtKind::While { condition, body } => {
+                    let start = self.label();
+                    let end = self.label();
+                    self.code.push(Instruction::Label(start));
+                    let condition = self.expression(condition);
+                    self.code.push(Instruction::IfFalse {
+                        condition,
+                        label: end,
+                    });
+                    self.block(body);
+                    self.code.push(Instruction::Goto(start));
+                    self.code.push(Instruction::Label(end));
+                }
+                StmtKind::Do { body, condition } => {
+                    let start = self.label();
+                    let end = self.label();
+                    self.code.push(Instruction::Label(start));
+                    self.block(body);
+                    let condition = self.expression(condition);
+                    self.code.push(Instruction::IfFalse {
+                        condition,
+                        label: end,
+                    });
+                    self.code.push(Instruction::Goto(start));
+                    self.code.push(Instruction::Label(end));
+                }
+                StmtKind::Assign {
+                    name,
+                    value,
+                    index: Some(index),
+                    ..
+                } => {
+                    let index = self.expression(index);
+                    let value = self.expression(value);
+                    self.code.push(Instruction::Store {
+                        array: self.variable(name),
+                        index,
+                        value,
+                    });
+                }
+                StmtKind::Assign { name, value, .. } => {
+                    let value = self.expression(value);
+                    self.code.push(Instruction::Copy {
+                        dest: Operand::Var(self.variable(name)),
+                        value,
+                    });
+                }
+                StmtKind::In { name } => self.code.push(Instruction::Read(self.variable(name))),
+                StmtKind::Out { expression } => {
+                    let value = self.expression(expression);
+                    self.code.push(Instruction::Write(value));
+                }
+                StmtKind::Return { value } => {
+                    let value = value.as_ref().map(|value| self.expression(value));
+                    self.code.push(Instruction::Return(value));
+                }
+                StmtKind::Call { expression } => {
+                    if let Node::Exp {
+                        kind: ExpKind::Call { name, args },
+                        ..
+                    } = &expression.node
+                    {
+                        self.call(name, args, false);
+                    }
+                }
+            },
+            Node::Exp { .. } => {
+                self.expression(node);
+            }
+        }
+    }
+
+    fn expression(&mut self, node: &TreeNode) -> Operand {
+        let Node::Exp { kind, .. } = &node.node else {
+            return Operand::Int(0);
+        };
+        match kind {
+            ExpKind::Const { value } => Operand::Int(*value),
+            ExpKind::ConstF { value } => Operand::Float(*value),
+            ExpKind::ConstB { value } => Operand::Bool(*value),
+            ExpKind::Id { name } => Operand::Var(self.variable(name)),
+            ExpKind::Call { name, args } => self.call(name, args, true).unwrap_or(Operand::Int(0)),
+            ExpKind::Index { name, index } => {
+                let index = self.expression(index);
+                let dest = self.temp();
+                self.code.push(Instruction::Load {
+                    dest: dest.clone(),
+                    array: self.variable(name),
+                    index,
+                });
+                dest
+            }
+            ExpKind::Op { op, left, right } => {
+                let left = self.expression(left);
+                let right = right.as_ref().map(|right| self.expression(right));
+                let dest = self.temp();
+                self.code.push(match right {
+                    Some(right) => Instruction::Binary {
+                        dest: dest.clone(),
+                        op: op.clone(),
+                        left,
+                        right,
+                    },
+                    None => Instruction::Unary {
+                        dest: dest.clone(),
+                        op: op.clone(),
+                        value: left,
+                    },
+                });
+                dest
+            }
+        }
+    }
+    // Los argumentos se calculan antes de pasarlos, así las llamadas anidadas no mezclan sus `param`
+    fn call(&mut self, name: &str, args: &[TreeNode], result: bool) -> Option<Operand> {
+        let values: Vec<_> = args.iter().map(|arg| self.expression(arg)).collect();
+        for value in values {
+            self.code.push(Instruction::Param(value));
+        }
+        let dest = result.then(|| self.temp());
+        self.code.push(Instruction::Call {
+            dest: dest.clone(),
+            name: name.to_string(),
+            args: args.len(),
+        });
+        dest
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+pub const CONFIG_FILE: &str = "vanilla.toml";
+
+// Versiones del lenguaje que entiende el compilador
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Edition {
+    #[default]
+    #[serde(rename = "2024")]
+    E2024,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+// Opciones por defecto de `build`; las banderas de la línea de comandos tienen prioridad
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildConfig {
+    pub verbose: bool,
+    pub analyze: bool,
+    pub symbols: bool,
+    pub symbols_format: Option<String>,
+    pub emit: Vec<String>,
+    pub out_dir: Option<PathBuf>,
+}
+
+// Contenido de `vanilla.toml`
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub edition: Edition,
+    // patrones glob de los archivos a compilar cuando no se dan en la línea de comandos
+    pub inputs: Vec<String>,
+    pub build: BuildConfig,
+    pub lints: BTreeMap<String, LintLevel>,
+    // directorio del archivo; las rutas de la configuración son relativas a él
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut config: Config = toml::from_str(&text).map_err(|e| e.to_string())?;
+        config.root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Ok(config)
+    }
+
+    // Busca `vanilla.toml` desde `start` hacia los directorios padre
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
+    // Los lints que no aparecen en la configuración son advertencias
+    pub fn lint_level(&self, lint: &str) -> LintLevel {
+        self.lints.get(lint).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    pub fn out_dir(&self) -> Option<PathBuf> {
+        self.build.out_dir.as_ref().map(|dir| self.root.join(dir))
+    }
+
+    // Archivos que coinciden con `inputs`, ordenados y sin repetir
+    pub fn input_files(&self) -> Result<Vec<String>, String> {
+        let mut files = vec![];
+        for pattern in self.inputs.iter() {
+            let full = self.root.join(pattern);
+            let paths = glob::glob(&full.to_string_lossy())
+                .map_err(|e| format!("Patrón inválido {}: {}", pattern, e))?;
+            for path in paths.flatten() {
+                if path.is_file() {
+                    files.push(path.to_string_lossy().to_string());
+                }
+            }
+        }
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+}
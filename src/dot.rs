@@ -0,0 +1,152 @@
+use crate::{
+    format::{format_float, operator_lexemme},
+    interpret::value_to_string,
+    structures::{DeclKind, ExpKind, Node, Program, StmtKind, TreeNode},
+    visit::{walk_program, BlockContext, Context, Edge, Visitor, Walk},
+};
+
+// Exporta el árbol a Graphviz. Cada nodo se une al primer hijo de cada bloque y los hermanos se
+// unen entre sí con aristas punteadas, como en los diagramas del libro
+pub fn program_to_dot(program: &Program) -> String {
+    let mut exporter = DotExporter {
+        out: String::new(),
+        previous: vec![],
+    };
+    exporter.out.push_str("digraph AST {\n");
+    exporter
+        .out
+        .push_str("    node [shape=box, fontname=\"Helvetica\"];\n");
+    exporter
+        .out
+        .push_str("    main [label=\"main\", shape=ellipse];\n");
+    walk_program(&mut exporter, program);
+    exporter.out.push_str("}\n");
+    exporter.out
+}
+
+struct DotExporter {
+    out: String,
+    // último nodo visitado de cada bloque abierto
+    previous: Vec<Option<String>>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn label(node: &TreeNode) -> String {
+    let mut lines = vec![];
+    match &node.node {
+        Node::Decl { kind, .. } => match kind {
+            DeclKind::Var { typ, name } => {
+                lines.push(format!("Decl {}", name));
+                lines.push(format!("{:?}", typ));
+            }
+            DeclKind::Array { typ, name, size } => {
+                lines.push(format!("Decl {}[{}]", name, size));
+                lines.push(format!("{:?}", typ));
+            }
+            DeclKind::Function {
+                name, return_type, ..
+            } => {
+                lines.push(format!("Function {}", name));
+                lines.push(format!("{:?}", return_type));
+            }
+        },
+        Node::Stmt { kind, .. } => match kind {
+            StmtKind::If { .. } => lines.push("If".to_string()),
+            StmtKind::While { .. } => lines.push("While".to_string()),
+            StmtKind::Do { .. } => lines.push("Do".to_string()),
+            StmtKind::Assign {
+                name,
+                exp_value,
+                index,
+                ..
+            } => {
+                match index {
+                    Some(_) => lines.push(format!("Assign {}[]", name)),
+                    None => lines.push(format!("Assign {}", name)),
+                }
+                if let Some(value) = exp_value {
+                    lines.push(format!("val = {}", value_to_string(value)));
+                }
+            }
+            StmtKind::In { name } => lines.push(format!("In {}", name)),
+            StmtKind::Out { .. } => lines.push("Out".to_string()),
+            StmtKind::Return { .. } => lines.push("Return".to_string()),
+            StmtKind::Call { .. } => lines.push("Call".to_string()),
+        },
+        Node::Exp { kind, typ, val, .. } => {
+            lines.push(match kind {
+                ExpKind::Op { op, .. } => format!("Op {}", operator_lexemme(op)),
+                ExpKind::Const { value } => format!("Const {}", value),
+                ExpKind::ConstF { value } => format!("Const {}", format_float(*value)),
+                ExpKind::ConstB { value } => format!("Const {}", value),
+                ExpKind::Id { name } => format!("Id {}", name),
+                ExpKind::Call { name, .. } => format!("Call {}", name),
+                ExpKind::Index { name, .. } => format!("Index {}", name),
+            });
+            lines.push(format!("{:?}", typ));
+            if let Some(value) = val {
+                lines.push(format!("val = {}", value_to_string(value)));
+            }
+        }
+    }
+    escape(&lines.join("\n")).replace('\n', "\\n")
+}
+
+fn is_block(edge: Edge) -> bool {
+    matches!(
+        edge,
+        Edge::Main | Edge::ThenBranch | Edge::ElseBranch | Edge::Body
+    )
+}
+
+impl Visitor for DotExporter {
+    fn enter_block(&mut self, _block: &[TreeNode], _ctx: &BlockContext) -> Walk {
+        self.previous.push(None);
+        Walk::Continue
+    }
+
+    fn exit_block(&mut self, _block: &[TreeNode], _ctx: &BlockContext) {
+        self.previous.pop();
+    }
+
+    fn enter_node(&mut self, node: &TreeNode, ctx: &Context) -> Walk {
+        let name = format!("n{}", ctx.id.0);
+        let shape = match node.node {
+            Node::Exp { .. } => "ellipse",
+            _ => "box",
+        };
+        self.out.push_str(&format!(
+            "    {} [label=\"{}\", shape={}];\n",
+            name,
+            label(node),
+            shape
+        ));
+        let parent = ctx
+            .parent
+            .map(|id| format!("n{}", id.0))
+            .unwrap_or_else(|| "main".to_string());
+        let sibling = if is_block(ctx.edge) {
+            self.previous
+                .last_mut()
+                .and_then(|previous| previous.replace(name.clone()))
+        } else {
+            None
+        };
+        match sibling {
+            Some(sibling) => self.out.push_str(&format!(
+                "    {} -> {} [label=\"sibling\", style=dashed];\n",
+                sibling, name
+            )),
+            None => self.out.push_str(&format!(
+                "    {} -> {} [label=\"{}\"];\n",
+                parent,
+                name,
+                ctx.edge.label()
+            )),
+        }
+        Walk::Continue
+    }
+}
@@ -0,0 +1,520 @@
+use scanner::data::{Token, TokenType};
+
+use crate::{
+    parse,
+    structures::{DeclKind, ExpKind, ExpType, Node, ParseError, Program, StmtKind, TreeNode},
+};
+
+const INDENT: &str = "    ";
+
+// Comentario encontrado en el código fuente, con la línea en la que empieza
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub lin: usize,
+    pub text: String,
+}
+
+// Obtiene los comentarios del código fuente. Las líneas empiezan en 1, igual que los cursores
+// del scanner
+pub fn extract_comments(source: &str) -> Vec<Comment> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut comments = vec![];
+    let mut lin = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '/' && next == Some('/') {
+            let start = i;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            comments.push(Comment {
+                lin,
+                text: text.trim_end().to_string(),
+            });
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            let start = i;
+            let start_lin = lin;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    lin += 1;
+                }
+                i += 1;
+            }
+            i = (i + 2).min(chars.len());
+            comments.push(Comment {
+                lin: start_lin,
+                text: chars[start..i].iter().collect(),
+            });
+            continue;
+        }
+        if c == '\n' {
+            lin += 1;
+        }
+        i += 1;
+    }
+    comments
+}
+
+// Parsea los tokens y regresa el código con formato canónico, o los errores de parseo
+pub fn format_source(source: &str, tokens: Vec<Token>) -> Result<String, Vec<ParseError>> {
+    let comments = extract_comments(source);
+    let braces = closing_braces(&tokens);
+    let (root, errors) = parse(tokens);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(format_program(
+        &root.unwrap_or_default(),
+        &comments,
+        &braces,
+    ))
+}
+
+// Líneas de las llaves que cierran bloques, en el orden del código. El árbol no las guarda y sin
+// ellas no se sabe si un comentario va antes o después del `}`
+pub fn closing_braces(tokens: &[Token]) -> Vec<usize> {
+    tokens
+        .iter()
+        .filter(|token| token.token_type == TokenType::RBRA)
+        .map(|token| token.start.lin)
+        .collect()
+}
+
+// Convierte el árbol de vuelta en código fuente. `braces` son las líneas de `closing_braces`; si
+// está vacío los comentarios del final de un bloque se escriben después de cerrarlo
+pub fn format_program(program: &Program, comments: &[Comment], braces: &[usize]) -> String {
+    let mut formatter = Formatter {
+        out: String::new(),
+        comments: comments.to_vec(),
+        next_comment: 0,
+        braces: braces.to_vec(),
+        next_brace: 0,
+        indent: 0,
+    };
+    formatter.comments.sort_by_key(|c| c.lin);
+    for function in program.functions.iter() {
+        formatter.function(function);
+    }
+    formatter.out.push_str("main {\n");
+    let brace = formatter.nested(&program.body);
+    formatter.out.push('}');
+    formatter.trailing_comments(brace);
+    formatter.out.push('\n');
+    // sin las llaves no se sabe dónde termina el main: los comentarios restantes quedan después
+    formatter.flush_comments(usize::MAX);
+    formatter.out
+}
+
+struct Formatter {
+    out: String,
+    comments: Vec<Comment>,
+    next_comment: usize,
+    braces: Vec<usize>,
+    next_brace: usize,
+    indent: usize,
+}
+
+impl Formatter {
+    fn line_start(&mut self) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    // Escribe los comentarios pendientes que empiezan antes de la línea indicada
+    fn flush_comments(&mut self, before: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.lin >= before {
+                break;
+            }
+            let text = comment.text.clone();
+            self.next_comment += 1;
+            self.line_start();
+            self.out.push_str(&text);
+            self.out.push('\n');
+        }
+    }
+
+    // Escribe los comentarios que están en la misma línea que la sentencia actual
+    fn trailing_comments(&mut self, lin: Option<usize>) {
+        let Some(lin) = lin else {
+            return;
+        };
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.lin != lin {
+                break;
+            }
+            self.out.push(' ');
+            self.out.push_str(&comment.text.clone());
+            self.next_comment += 1;
+        }
+    }
+
+    // Las funciones van antes del main, separadas por una línea en blanco
+    fn function(&mut self, node: &TreeNode) {
+        let Node::Decl {
+            kind:
+                DeclKind::Function {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                },
+            cursor,
+            ..
+        } = &node.node
+        else {
+            return;
+        };
+        let lin = cursor.as_ref().map(|c| c.lin);
+        if let Some(lin) = lin {
+            self.flush_comments(lin);
+        }
+        let params: Vec<_> = params
+            .iter()
+            .filter_map(|param| match &param.node {
+                Node::Decl {
+                    kind: DeclKind::Var { typ, name },
+                    ..
+                } => Some(format!("{} {}", type_keyword(typ), name)),
+                _ => None,
+            })
+            .collect();
+        self.out.push_str(&format!(
+            "{} {}({}) {{",
+            type_keyword(return_type),
+            name,
+            params.join(", ")
+        ));
+        self.trailing_comments(lin);
+        self.out.push('\n');
+        let brace = self.nested(body);
+        self.out.push('}');
+        self.trailing_comments(brace);
+        self.out.push_str("\n\n");
+    }
+
+    fn block(&mut self, nodes: &[TreeNode]) {
+        let mut index = 0;
+        while index < nodes.len() {
+            let node = &nodes[index];
+            index += match &node.node {
+                Node::Decl { .. } => self.declaration(&nodes[index..]),
+                Node::Stmt { kind, cursor, .. } => {
+                    let lin = cursor.as_ref().map(|c| c.lin);
+                    if let Some(lin) = lin {
+                        self.flush_comments(lin);
+                    }
+                    self.statement(kind, lin);
+                    1
+                }
+                Node::Exp { .. } => 1, // no debería haber expresiones sueltas
+            };
+        }
+    }
+
+    // Agrupa las declaraciones consecutivas del mismo tipo escritas en la misma línea.
+    // Regresa cuántos nodos se escribieron
+    fn declaration(&mut self, nodes: &[TreeNode]) -> usize {
+        let Some((typ, name)) = declared(&nodes[0]) else {
+            return 1;
+        };
+        let lin = nodes[0].node.cursor().map(|c| c.lin);
+        if let Some(lin) = lin {
+            self.flush_comments(lin);
+        }
+        let mut names = vec![name];
+        for node in nodes[1..].iter() {
+            let Some((next_typ, name)) = declared(node) else {
+                break;
+            };
+            if next_typ != typ || node.node.cursor().map(|c| c.lin) != lin {
+                break;
+            }
+            names.push(name);
+        }
+        self.line_start();
+        self.out
+            .push_str(&format!("{} {};", type_keyword(typ), names.join(", ")));
+        self.trailing_comments(lin);
+        self.out.push('\n');
+        names.len()
+    }
+
+    fn statement(&mut self, kind: &StmtKind, lin: Option<usize>) {
+        self.line_start();
+        match kind {
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.out
+                    .push_str(&format!("if ({}) {{", expression(condition, 0)));
+                self.trailing_comments(lin);
+                self.out.push('\n');
+                let mut brace = self.nested(then_branch);
+                self.line_start();
+                if let Some(else_branch) = else_branch {
+                    self.out.push_str("} else {");
+                    self.trailing_comments(brace);
+                    self.out.push('\n');
+                    brace = self.nested(else_branch);
+                    self.line_start();
+                }
+                self.out.push('}');
+                self.trailing_comments(brace);
+                self.out.push('\n');
+            }
+            StmtKind::While { condition, body } => {
+                self.out
+                    .push_str(&format!("while ({}) {{", expression(condition, 0)));
+                self.trailing_comments(lin);
+                self.out.push('\n');
+                let brace = self.nested(body);
+                self.line_start();
+                self.out.push('}');
+                self.trailing_comments(brace);
+                self.out.push('\n');
+            }
+            StmtKind::Do { body, condition } => {
+                self.out.push_str("do {");
+                self.trailing_comments(lin);
+                self.out.push('\n');
+                let brace = self.nested(body);
+                self.line_start();
+                self.out
+                    .push_str(&format!("}} while ({});", expression(condition, 0)));
+                self.trailing_comments(brace);
+                self.out.push('\n');
+            }
+            StmtKind::Assign {
+                name,
+                value,
+                index: Some(index),
+                ..
+            } => {
+                self.out.push_str(&format!(
+                    "{}[{}] = {};",
+                    name,
+                    expression(index, 0),
+                    expression(value, 0)
+                ));
+                self.trailing_comments(lin);
+                self.out.push('\n');
+            }
+            StmtKind::Assign { name, value, .. } => {
+                match increment(value) {
+                    Some((id, op)) if id == name => {
+                        self.out.push_str(&format!("{}{};", name, op));
+                    }
+                    _ => self
+                        .out
+                        .push_str(&format!("{} = {};", name, expression(value, 0))),
+                }
+                self.trailing_comments(lin);
+                self.out.push('\n');
+            }
+            StmtKind::In { name } => {
+                self.out.push_str(&format!("stdin {};", name));
+                self.trailing_comments(lin);
+                self.out.push('\n');
+            }
+            StmtKind::Out { expression: exp } => {
+                self.out
+                    .push_str(&format!("stdout {};", expression(exp, 0)));
+                self.trailing_comments(lin);
+                self.out.push('\n');
+            }
+            StmtKind::Return { value } => {
+                match value {
+                    Some(value) => {
+                        self.out
+                            .push_str(&format!("{} {};", parse::RETURN, expression(value, 0)))
+                    }
+                    None => self.out.push_str(&format!("{};", parse::RETURN)),
+                }
+                self.trailing_comments(lin);
+                self.out.push('\n');
+            }
+            StmtKind::Call { expression: exp } => {
+                self.out.push_str(&format!("{};", expression(exp, 0)));
+                self.trailing_comments(lin);
+                self.out.push('\n');
+            }
+        }
+    }
+
+    // Escribe el bloque y los comentarios que quedan antes de su `}`, con la sangría de adentro.
+    // Regresa la línea del `}`, si se conoce
+    fn nested(&mut self, block: &[TreeNode]) -> Option<usize> {
+        self.indent += 1;
+        self.block(block);
+        let brace = self.braces.get(self.next_brace).copied();
+        self.next_brace += 1;
+        if let Some(lin) = brace {
+            self.flush_comments(lin);
+        }
+        self.indent -= 1;
+        brace
+    }
+}
+
+// Tipo y nombre (con su tamaño, si es arreglo) de una declaración de variable
+fn declared(node: &TreeNode) -> Option<(&ExpType, String)> {
+    match &node.node {
+        Node::Decl {
+            kind: DeclKind::Var { typ, name },
+            ..
+        } => Some((typ, name.clone())),
+        Node::Decl {
+            kind: DeclKind::Array { typ, name, size },
+            ..
+        } => Some((typ, format!("{}[{}]", name, size))),
+        _ => None,
+    }
+}
+
+fn type_keyword(typ: &ExpType) -> &'static str {
+    match typ {
+        ExpType::Integer => "integer",
+        ExpType::Float => "double",
+        ExpType::Boolean => parse::BOOLEAN,
+        ExpType::Void => parse::VOID,
+    }
+}
+
+// Nivel de precedencia de cada operador, siguiendo la gramática del parser
+fn precedence(op: &TokenType) -> u8 {
+    match op {
+        TokenType::OR => 1,
+        TokenType::AND => 2,
+        TokenType::NEG => 3,
+        TokenType::LT
+        | TokenType::LE
+        | TokenType::GT
+        | TokenType::GE
+        | TokenType::EQ
+        | TokenType::NE => 4,
+        TokenType::SUM | TokenType::MIN => 5,
+        TokenType::TIMES | TokenType::DIV | TokenType::MODULUS => 6,
+        TokenType::POWER => 7,
+        _ => 8,
+    }
+}
+
+pub fn operator_lexemme(op: &TokenType) -> &'static str {
+    match op {
+        TokenType::OR => "or",
+        TokenType::AND => "and",
+        TokenType::NEG => "!",
+        TokenType::LT => "<",
+        TokenType::LE => "<=",
+        TokenType::GT => ">",
+        TokenType::GE => ">=",
+        TokenType::EQ => "==",
+        TokenType::NE => "!=",
+        TokenType::SUM => "+",
+        TokenType::MIN => "-",
+        TokenType::TIMES => "*",
+        TokenType::DIV => "/",
+        TokenType::MODULUS => "%",
+        TokenType::POWER => "^",
+        _ => "?",
+    }
+}
+
+// Los `id++` e `id--` se guardan como `id + 1` con una constante sin cursor
+fn increment(node: &TreeNode) -> Option<(&String, &'static str)> {
+    if let Node::Exp {
+        kind:
+            ExpKind::Op {
+                op,
+                left,
+                right: Some(right),
+            },
+        ..
+    } = &node.node
+    {
+        if let (
+            Node::Exp {
+                kind: ExpKind::Id { name },
+                ..
+            },
+            Node::Exp {
+                kind: ExpKind::Const { value: 1 },
+                cursor: None,
+                ..
+            },
+        ) = (&left.node, &right.node)
+        {
+            return match op {
+                TokenType::SUM => Some((name, "++")),
+                TokenType::MIN => Some((name, "--")),
+                _ => None,
+            };
+        }
+    }
+    None
+}
+
+pub fn format_float(value: f32) -> String {
+    let text = value.to_string();
+    if text.contains('.') || text.contains('e') || !value.is_finite() {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+// Escribe la expresión, agregando paréntesis si su precedencia es menor que la mínima
+pub fn expression(node: &TreeNode, min_level: u8) -> String {
+    let Node::Exp { kind, .. } = &node.node else {
+        return String::new();
+    };
+    match kind {
+        ExpKind::Const { value } => value.to_string(),
+        ExpKind::ConstF { value } => format_float(*value),
+        ExpKind::ConstB { value } => value.to_string(),
+        ExpKind::Id { name } => name.clone(),
+        ExpKind::Call { name, args } => {
+            let args: Vec<_> = args.iter().map(|arg| expression(arg, 0)).collect();
+            format!("{}({})", name, args.join(", "))
+        }
+        ExpKind::Index { name, index } => format!("{}[{}]", name, expression(index, 0)),
+        ExpKind::Op { op, left, right } => {
+            if let Some((name, op)) = increment(node) {
+                return format!("{}{}", name, op);
+            }
+            let level = precedence(op);
+            let text = match right {
+                None => format!("{}{}", operator_lexemme(op), expression(left, 4)),
+                Some(right) => {
+                    // los operadores relacionales y lógicos no se pueden encadenar
+                    let left_level = match level {
+                        1..=4 => level + 1,
+                        _ => level,
+                    };
+                    format!(
+                        "{} {} {}",
+                        expression(left, left_level),
+                        operator_lexemme(op),
+                        expression(right, level + 1)
+                    )
+                }
+            };
+            if level < min_level {
+                format!("({})", text)
+            } else {
+                text
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{fs, io, path::Path};
+
+use crate::{
+    interpret::{Interpreter, MAX_INSTRUCTIONS},
+    session::Session,
+    source::position,
+};
+
+// Anotaciones que se escriben como comentarios en los archivos de prueba:
+//   x = 1.5; // ERROR: Se asignó un tipo Float   error esperado en esa línea
+//   // ERROR: Se esperaba un token               error sin posición (por ejemplo al final)
+//   // STDIN: 5                                  línea de entrada para el programa
+//   // STDOUT: 10                                línea de salida esperada
+const ERROR: &str = "// ERROR:";
+const STDIN: &str = "// STDIN:";
+const STDOUT: &str = "// STDOUT:";
+
+// Error esperado u obtenido; la línea (desde 0) es None para los que no tienen posición
+#[derive(Debug, Clone, PartialEq)]
+struct Expected {
+    line: Option<usize>,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+struct Expectations {
+    errors: Vec<Expected>,
+    stdin: Vec<String>,
+    stdout: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default)]
+struct Outcome {
+    errors: Vec<Expected>,
+    stdout: Option<Vec<String>>,
+}
+
+// Resultado de un archivo de prueba; sin fallas la prueba pasa
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenResult {
+    pub file: String,
+    pub failures: Vec<String>,
+}
+
+impl GoldenResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+fn annotation<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    line.trim_start().strip_prefix(marker).map(str::trim)
+}
+
+fn parse_expectations(source: &str) -> Expectations {
+    let mut expectations = Expectations::default();
+    for (index, line) in source.lines().enumerate() {
+        if let Some(text) = annotation(line, STDIN) {
+            expectations.stdin.push(text.to_string());
+            continue;
+        }
+        if let Some(text) = annotation(line, STDOUT) {
+            expectations
+                .stdout
+                .get_or_insert_with(Vec::new)
+                .push(text.to_string());
+            continue;
+        }
+        let standalone = line.trim_start().starts_with(ERROR);
+        for text in line.split(ERROR).skip(1) {
+            expectations.errors.push(Expected {
+                line: if standalone { None } else { Some(index) },
+                message: text.trim().to_string(),
+            });
+        }
+    }
+    expectations
+}
+
+// Compila el texto y, si no tiene errores, lo ejecuta con la entrada dada
+fn run_source(source: &str, stdin: &[String]) -> Outcome {
+    let mut session = Session::from_source("<golden>", source);
+    session.check();
+    let mut outcome = Outcome::default();
+    for diagnostic in session.diagnostics() {
+        outcome.errors.push(Expected {
+            line: diagnostic.cursor.as_ref().map(|cursor| position(cursor).0),
+            message: diagnostic.message.clone(),
+        });
+    }
+    if !outcome.errors.is_empty() {
+        return outcome;
+    }
+    if let Some(program) = session.analyzed_program() {
+        let input = stdin
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        let mut output = vec![];
+        let result = Interpreter::new().with_limit(MAX_INSTRUCTIONS).run(
+            program,
+            &mut input.as_bytes(),
+            &mut output,
+        );
+        if let Err(error) = result {
+            outcome.errors.push(Expected {
+                line: error.cursor.as_ref().map(|cursor| position(cursor).0),
+                message: error.message,
+            });
+        }
+        outcome.stdout = Some(
+            String::from_utf8_lossy(&output)
+                .lines()
+                .map(str::to_string)
+                .collect(),
+        );
+    }
+    outcome
+}
+
+fn describe_line(line: Option<usize>) -> String {
+    match line {
+        Some(line) => format!("línea {}", line + 1),
+        None => "sin posición".to_string(),
+    }
+}
+
+// Compara el archivo con sus anotaciones
+pub fn run_golden(path: &Path) -> GoldenResult {
+    let mut result = GoldenResult {
+        file: path.to_string_lossy().to_string(),
+        failures: vec![],
+    };
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            result.failures.push(format!("No se pudo leer: {}", e));
+            return result;
+        }
+    };
+    let expectations = parse_expectations(&source);
+    let outcome = run_source(&source, &expectations.stdin);
+
+    let mut unmatched = outcome.errors.clone();
+    for expected in expectations.errors.iter() {
+        let found = unmatched.iter().position(|actual| {
+            actual.line == expected.line && actual.message.contains(&expected.message)
+        });
+        match found {
+            Some(index) => {
+                unmatched.remove(index);
+            }
+            None => result.failures.push(format!(
+                "{}: se esperaba el error \"{}\"",
+                describe_line(expected.line),
+                expected.message
+            )),
+        }
+    }
+    for actual in unmatched {
+        result.failures.push(format!(
+            "{}: error inesperado \"{}\"",
+            describe_line(actual.line),
+            actual.message
+        ));
+    }
+    if let (Some(expected), Some(actual)) = (&expectations.stdout, &outcome.stdout) {
+        if expected != actual {
+            result.failures.push(format!(
+                "la salida no coincide\n  esperada: {:?}\n  obtenida: {:?}",
+                expected, actual
+            ));
+        }
+    }
+    result
+}
+
+// Quita las anotaciones de errores y de salida; las de entrada se conservan
+fn strip_annotations(source: &str) -> Vec<String> {
+    source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with(ERROR) && annotation(line, STDOUT).is_none())
+        .map(|line| match line.find(ERROR) {
+            Some(index) => line[..index].trim_end().to_string(),
+            None => line.to_string(),
+        })
+        .collect()
+}
+
+// Reescribe las anotaciones del archivo con los resultados actuales. Regresa si el archivo cambió
+pub fn bless_golden(path: &Path) -> io::Result<bool> {
+    let source = fs::read_to_string(path)?;
+    let expectations = parse_expectations(&source);
+    let mut lines = strip_annotations(&source);
+    let stripped = lines.join("\n") + "\n";
+    let outcome = run_source(&stripped, &expectations.stdin);
+
+    let mut standalone = vec![];
+    for error in outcome.errors.iter() {
+        match error.line.filter(|line| *line < lines.len()) {
+            Some(line) => lines[line].push_str(&format!(" {} {}", ERROR, error.message)),
+            None => standalone.push(format!("{} {}", ERROR, error.message)),
+        }
+    }
+    lines.extend(standalone);
+    let stdout = outcome.stdout.unwrap_or_default();
+    if !stdout.is_empty() || expectations.stdout.is_some() {
+        lines.extend(stdout.iter().map(|line| format!("{} {}", STDOUT, line)));
+    }
+    let blessed = lines.join("\n") + "\n";
+    if blessed == source {
+        return Ok(false);
+    }
+    fs::write(path, blessed)?;
+    Ok(true)
+}
@@ -0,0 +1,542 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io::{BufRead, Write},
+    rc::Rc,
+};
+
+use scanner::data::{Cursor, TokenType};
+
+use crate::{
+    format::format_float,
+    structures::{DeclKind, ExpKind, ExpType, Node, NodeValue, Program, StmtKind, TreeNode},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub cursor: Option<Cursor>,
+}
+
+impl RuntimeError {
+    fn new(node: &TreeNode, message: &str) -> Self {
+        RuntimeError {
+            message: message.to_string(),
+            cursor: node.node.cursor().cloned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub typ: ExpType,
+    pub value: NodeValue,
+    // elementos de los arreglos, vacío para las variables
+    pub elements: Vec<NodeValue>,
+}
+
+// Llamadas anidadas permitidas antes de detener el programa, para que una recursión sin fin no
+// desborde la pila del intérprete
+pub const MAX_CALL_DEPTH: usize = 256;
+
+// Instrucciones que ejecutan `watch --run` y las pruebas golden antes de detener el programa, para
+// que un ciclo sin fin no los deje colgados
+pub const MAX_INSTRUCTIONS: usize = 10_000_000;
+
+// Ejecuta el árbol ya analizado. Las variables se conservan entre llamadas
+#[derive(Debug, Clone, Default)]
+pub struct Interpreter {
+    variables: HashMap<String, Variable>,
+    // por cada bloque abierto, las variables que ocultó y su valor de afuera (None si no existía)
+    scopes: Vec<Vec<(String, Option<Variable>)>>,
+    functions: HashMap<String, Rc<TreeNode>>,
+    // valor del `return` que se está ejecutando; mientras haya uno no se ejecutan más sentencias
+    returning: Option<Option<NodeValue>>,
+    depth: usize,
+    // instrucciones ejecutadas y cuántas se permiten (None si no hay límite)
+    steps: usize,
+    limit: Option<usize>,
+}
+
+pub fn value_to_string(value: &NodeValue) -> String {
+    match value {
+        NodeValue::Int(value) => value.to_string(),
+        NodeValue::Float(value) => format_float(*value),
+        NodeValue::Boolean(value) => value.to_string(),
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter::default()
+    }
+
+    // Detiene la ejecución con un error después de `limit` instrucciones (sentencias y
+    // condiciones evaluadas)
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn variables(&self) -> &HashMap<String, Variable> {
+        &self.variables
+    }
+
+    pub fn value(&self, name: &str) -> Option<&NodeValue> {
+        self.variables.get(name).map(|variable| &variable.value)
+    }
+
+    pub fn run(
+        &mut self,
+        program: &Program,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), RuntimeError> {
+        for function in program.functions.iter() {
+            if let Node::Decl {
+                kind: DeclKind::Function { name, .. },
+                ..
+            } = &function.node
+            {
+                self.functions
+                    .insert(name.clone(), Rc::new(function.clone()));
+            }
+        }
+        self.execute(&program.body, input, output)
+    }
+
+    pub fn execute(
+        &mut self,
+        block: &[TreeNode],
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), RuntimeError> {
+        for node in block.iter() {
+            self.statement(node, input, output)?;
+            if self.returning.is_some() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Ejecuta el cuerpo de un if, while o do. Al terminar se quitan sus variables y las de afuera
+    // que ocultó recuperan su valor
+    fn block(
+        &mut self,
+        block: &[TreeNode],
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), RuntimeError> {
+        self.scopes.push(vec![]);
+        let result = self.execute(block, input, output);
+        for (name, outer) in self.scopes.pop().unwrap_or_default().into_iter().rev() {
+            match outer {
+                Some(variable) => self.variables.insert(name, variable),
+                None => self.variables.remove(&name),
+            };
+        }
+        result
+    }
+
+    fn statement(
+        &mut self,
+        node: &TreeNode,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), RuntimeError> {
+        self.step(node)?;
+        match &node.node {
+            Node::Decl {
+                kind: DeclKind::Var { typ, name },
+                ..
+            } => self.declare(typ, name, 0),
+            Node::Decl {
+                kind: DeclKind::Array { typ, name, size },
+                ..
+            } => self.declare(typ, name, *size),
+            // las funciones se registran al empezar `run`
+            Node::Decl { .. } => {}
+            Node::Stmt { kind, .. } => match kind {
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } => {
+                    if self.condition(condition, input, output)? {
+                        self.block(then_branch, input, output)?;
+                    } else if let Some(else_branch) = else_branch {
+                        self.block(else_branch, input, output)?;
+                    }
+                }
+                StmtKind::While { condition, body } => {
+                    while self.returning.is_none() && self.condition(condition, input, output)? {
+                        self.block(body, input, output)?;
+                    }
+                }
+                StmtKind::Do { body, condition } => loop {
+                    self.block(body, input, output)?;
+                    if self.returning.is_some() || !self.condition(condition, input, output)? {
+                        break;
+                    }
+                },
+                StmtKind::Assign {
+                    name,
+                    value,
+                    index: Some(index),
+                    ..
+                } => {
+                    let position = self.position(name, index, input, output)?;
+                    let value = self.evaluate(value, input, output)?;
+                    let variable = self.variables.get_mut(name).unwrap(); // `position` lo verificó
+                    variable.elements[position] = convert(node, &variable.typ, value)?;
+                }
+                StmtKind::Assign { name, value, .. } => {
+                    let value = self.evaluate(value, input, output)?;
+                    self.assign(node, name, value)?;
+                }
+                StmtKind::In { name } => {
+                    let mut line = String::new();
+                    let read = input
+                        .read_line(&mut line)
+                        .map_err(|e| RuntimeError::new(node, &e.to_string()))?;
+                    if read == 0 {
+                        return Err(RuntimeError::new(node, "No hay más datos en la entrada"));
+                    }
+                    let line = line.trim();
+                    let value = match self.variables.get(name).map(|v| &v.typ) {
+                        Some(ExpType::Float) => line.parse().ok().map(NodeValue::Float),
+                        Some(ExpType::Boolean) => line.parse().ok().map(NodeValue::Boolean),
+                        _ => line.parse().ok().map(NodeValue::Int),
+                    };
+                    match value {
+                        Some(value) => self.assign(node, name, value)?,
+                        None => {
+                            return Err(RuntimeError::new(
+                                node,
+                                &format!("Valor inválido para {}: {}", name, line),
+                            ))
+                        }
+                    }
+                }
+                StmtKind::Out { expression } => {
+                    let value = self.evaluate(expression, input, output)?;
+                    writeln!(output, "{}", value_to_string(&value))
+                        .map_err(|e| RuntimeError::new(node, &e.to_string()))?;
+                }
+                StmtKind::Return { value } => {
+                    let value = match value {
+                        Some(value) => Some(self.evaluate(value, input, output)?),
+                        None => None,
+                    };
+                    self.returning = Some(value);
+                }
+                StmtKind::Call { expression } => {
+                    self.evaluate(expression, input, output)?;
+                }
+            },
+            Node::Exp { .. } => {
+                self.evaluate(node, input, output)?;
+            }
+        }
+        Ok(())
+    }
+
+    // `size` es el número de elementos de un arreglo, 0 para las variables
+    fn declare(&mut self, typ: &ExpType, name: &str, size: usize) {
+        let value = match typ {
+            ExpType::Float => NodeValue::Float(0.0),
+            ExpType::Boolean => NodeValue::Boolean(false),
+            _ => NodeValue::Int(0),
+        };
+        let elements = vec![value.clone(); size];
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push((name.to_string(), self.variables.get(name).cloned()));
+        }
+        self.variables.insert(
+            name.to_string(),
+            Variable {
+                typ: typ.clone(),
+                value,
+                elements,
+            },
+        );
+    }
+
+    // Cada llamada tiene sus propias variables; las del llamador se guardan y se recuperan al
+    // regresar
+    fn call(
+        &mut self,
+        node: &TreeNode,
+        name: &str,
+        args: &[TreeNode],
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<NodeValue, RuntimeError> {
+        let Some(function) = self.functions.get(name).cloned() else {
+            return Err(RuntimeError::new(
+                node,
+                &format!("La función {} no está declarada", name),
+            ));
+        };
+        let Node::Decl {
+            kind:
+                DeclKind::Function {
+                    params,
+                    return_type,
+                    body,
+                    ..
+                },
+            ..
+        } = &function.node
+        else {
+            return Err(RuntimeError::new(node, "Se esperaba una función"));
+        };
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(
+                node,
+                &format!("Demasiadas llamadas anidadas ({})", MAX_CALL_DEPTH),
+            ));
+        }
+        let mut values = vec![];
+        for arg in args.iter() {
+            values.push(self.evaluate(arg, input, output)?);
+        }
+
+        let variables = std::mem::take(&mut self.variables);
+        let scopes = std::mem::take(&mut self.scopes);
+        let mut result = Ok(());
+        for (param, value) in params.iter().zip(values) {
+            if let Node::Decl {
+                kind: DeclKind::Var { typ, name },
+                ..
+            } = &param.node
+            {
+                self.declare(typ, name, 0);
+                result = result.and_then(|_| self.assign(node, name, value));
+            }
+        }
+        self.depth += 1;
+        let result = result.and_then(|_| self.execute(body, input, output));
+        self.depth -= 1;
+        let returned = self.returning.take().flatten();
+        self.variables = variables;
+        self.scopes = scopes;
+        result?;
+
+        match (returned, return_type) {
+            (Some(NodeValue::Int(value)), ExpType::Float) => Ok(NodeValue::Float(value as f32)),
+            (Some(value), _) => Ok(value),
+            // el valor de una función void no se usa; el análisis no deja usarla en expresiones
+            (None, ExpType::Void) => Ok(NodeValue::Int(0)),
+            (None, _) => Err(RuntimeError::new(
+                node,
+                &format!("La función {} terminó sin regresar un valor", name),
+            )),
+        }
+    }
+
+    fn assign(
+        &mut self,
+        node: &TreeNode,
+        name: &str,
+        value: NodeValue,
+    ) -> Result<(), RuntimeError> {
+        let Some(variable) = self.variables.get_mut(name) else {
+            return Err(RuntimeError::new(
+                node,
+                &format!("La variable {} no está declarada", name),
+            ));
+        };
+        variable.value = convert(node, &variable.typ, value)?;
+        Ok(())
+    }
+
+    // Posición del elemento `name[index]`, verificando que esté dentro del arreglo
+    fn position(
+        &mut self,
+        name: &str,
+        index: &TreeNode,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<usize, RuntimeError> {
+        let value = self.evaluate(index, input, output)?;
+        let Some(size) = self.variables.get(name).map(|v| v.elements.len()) else {
+            return Err(RuntimeError::new(
+                index,
+                &format!("La variable {} no está declarada", name),
+            ));
+        };
+        match value {
+            NodeValue::Int(position) if position >= 0 && (position as usize) < size => {
+                Ok(position as usize)
+            }
+            NodeValue::Int(position) => Err(RuntimeError::new(
+                index,
+                &format!(
+                    "El índice {} está fuera del arreglo {} de tamaño {}",
+                    position, name, size
+                ),
+            )),
+            _ => Err(RuntimeError::new(index, "El índice no es entero")),
+        }
+    }
+
+    // Cuenta una instrucción; con límite, falla en cuanto se pasa de él
+    fn step(&mut self, node: &TreeNode) -> Result<(), RuntimeError> {
+        self.steps += 1;
+        match self.limit {
+            Some(limit) if self.steps > limit => Err(RuntimeError::new(
+                node,
+                &format!("Se excedió el límite de {} instrucciones", limit),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn condition(
+        &mut self,
+        node: &TreeNode,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<bool, RuntimeError> {
+        self.step(node)?;
+        match self.evaluate(node, input, output)? {
+            NodeValue::Boolean(value) => Ok(value),
+            _ => Err(RuntimeError::new(node, "La condición no es booleana")),
+        }
+    }
+
+    // La entrada y la salida son para las funciones que se llamen dentro de la expresión
+    pub fn evaluate(
+        &mut self,
+        node: &TreeNode,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<NodeValue, RuntimeError> {
+        let Node::Exp { kind, .. } = &node.node else {
+            return Err(RuntimeError::new(node, "Se esperaba una expresión"));
+        };
+        match kind {
+            ExpKind::Const { value } => Ok(NodeValue::Int(*value)),
+            ExpKind::ConstF { value } => Ok(NodeValue::Float(*value)),
+            ExpKind::ConstB { value } => Ok(NodeValue::Boolean(*value)),
+            ExpKind::Id { name } => self.value(name).cloned().ok_or_else(|| {
+                RuntimeError::new(node, &format!("La variable {} no está declarada", name))
+            }),
+            ExpKind::Call { name, args } => self.call(node, name, args, input, output),
+            ExpKind::Index { name, index } => {
+                let position = self.position(name, index, input, output)?;
+                Ok(self.variables[name].elements[position].clone())
+            }
+            ExpKind::Op { op, left, right } => {
+                let left_val = self.evaluate(left, input, output)?;
+                let Some(right) = right else {
+                    return match (op, left_val) {
+                        (TokenType::NEG, NodeValue::Boolean(value)) => {
+                            Ok(NodeValue::Boolean(!value))
+                        }
+                        _ => Err(RuntimeError::new(
+                            node,
+                            "Operacion de negacion solo esta disponible para booleanos",
+                        )),
+                    };
+                };
+                // evaluación en corto circuito
+                match (op, &left_val) {
+                    (TokenType::AND, NodeValue::Boolean(false)) => {
+                        return Ok(NodeValue::Boolean(false))
+                    }
+                    (TokenType::OR, NodeValue::Boolean(true)) => {
+                        return Ok(NodeValue::Boolean(true))
+                    }
+                    _ => {}
+                }
+                let right_val = self.evaluate(right, input, output)?;
+                operation(node, op, left_val, right_val)
+            }
+        }
+    }
+}
+
+// Valor que se guarda en una variable o elemento del tipo `typ`
+fn convert(node: &TreeNode, typ: &ExpType, value: NodeValue) -> Result<NodeValue, RuntimeError> {
+    match (typ, value) {
+        (ExpType::Float, NodeValue::Int(value)) => Ok(NodeValue::Float(value as f32)),
+        (ExpType::Integer, NodeValue::Float(_)) => Err(RuntimeError::new(
+            node,
+            "La asignación de un flotante a un entero no es válida",
+        )),
+        (_, value) => Ok(value),
+    }
+}
+
+fn operation(
+    node: &TreeNode,
+    op: &TokenType,
+    left: NodeValue,
+    right: NodeValue,
+) -> Result<NodeValue, RuntimeError> {
+    let result = match (op, &left, &right) {
+        (TokenType::DIV | TokenType::MODULUS, _, NodeValue::Int(0)) => {
+            return Err(RuntimeError::new(node, "División entre cero"))
+        }
+        // las operaciones entre enteros se verifican para no desbordar
+        (_, NodeValue::Int(l), NodeValue::Int(r)) if is_arithmetic(op) => {
+            let (l, r) = (*l, *r);
+            let value = match op {
+                TokenType::SUM => l.checked_add(r),
+                TokenType::MIN => l.checked_sub(r),
+                TokenType::TIMES => l.checked_mul(r),
+                TokenType::DIV => l.checked_div(r),
+                TokenType::MODULUS => l.checked_rem(r),
+                _ => u32::try_from(r).ok().and_then(|r| l.checked_pow(r)),
+            };
+            return value
+                .map(NodeValue::Int)
+                .ok_or_else(|| RuntimeError::new(node, "Desbordamiento en una operación entera"));
+        }
+        (TokenType::SUM, ..) => left + right,
+        (TokenType::MIN, ..) => left - right,
+        (TokenType::TIMES, ..) => left * right,
+        (TokenType::DIV, ..) => left / right,
+        (TokenType::MODULUS, ..) => left % right,
+        (TokenType::POWER, ..) => left.pow(right),
+        (TokenType::LT, ..) => compare(&left, &right, Ordering::is_lt),
+        (TokenType::LE, ..) => compare(&left, &right, Ordering::is_le),
+        (TokenType::GT, ..) => compare(&left, &right, Ordering::is_gt),
+        (TokenType::GE, ..) => compare(&left, &right, Ordering::is_ge),
+        (TokenType::EQ, ..) => Some(NodeValue::Boolean(left == right)),
+        (TokenType::NE, ..) => Some(NodeValue::Boolean(left != right)),
+        (TokenType::AND, NodeValue::Boolean(l), NodeValue::Boolean(r)) => {
+            Some(NodeValue::Boolean(*l && *r))
+        }
+        (TokenType::OR, NodeValue::Boolean(l), NodeValue::Boolean(r)) => {
+            Some(NodeValue::Boolean(*l || *r))
+        }
+        _ => None,
+    };
+    result.ok_or_else(|| {
+        RuntimeError::new(
+            node,
+            &format!("La operación {:?} no está definida para estos valores", op),
+        )
+    })
+}
+
+fn is_arithmetic(op: &TokenType) -> bool {
+    matches!(
+        op,
+        TokenType::SUM
+            | TokenType::MIN
+            | TokenType::TIMES
+            | TokenType::DIV
+            | TokenType::MODULUS
+            | TokenType::POWER
+    )
+}
+
+fn compare(left: &NodeValue, right: &NodeValue, test: fn(Ordering) -> bool) -> Option<NodeValue> {
+    left.partial_cmp(right)
+        .map(|ordering| NodeValue::Boolean(test(ordering)))
+}
@@ -1,4 +1,21 @@
 pub mod analyze;
+pub mod codegen;
+pub mod config;
+pub mod dot;
+pub mod format;
+pub mod golden;
+pub mod interpret;
 pub mod parse;
+pub mod rename;
+pub mod repl;
+pub mod schema;
+pub mod session;
+pub mod source;
+pub mod symbols;
 pub use analyze::*;
+pub use dot::program_to_dot;
+pub use format::{format_program, format_source};
 pub use parse::*;
+pub use rename::{rename_symbol, RenameError};
+pub use session::{analyze_str, parse_str, Diagnostic, Session, Stage};
+pub use symbols::{format_symbol_table, SymbolFormat};
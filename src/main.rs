@@ -1,179 +1,1220 @@
-use std::{fs, io::Write, path::Path};
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
 
-use clap::{Args, Parser, Subcommand};
-use parser::{parse, utils::print_sym_table, Analyzer};
-use scanner::tokenize_file;
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use parser::{
+    codegen::{generate_tac, tac_to_string},
+    config::Config,
+    format_source, format_symbol_table,
+    golden::{bless_golden, run_golden},
+    interpret::{value_to_string, Interpreter, MAX_INSTRUCTIONS},
+    parse, program_to_dot, rename_symbol,
+    repl::{Repl, ReplError, ReplOutput},
+    schema::{program_from_json, AstDocument},
+    session::STDIN_NAME,
+    source::{tokenize_file, tokenize_str},
+    structures::{AnalyzeError, ParseError, Program, SymbolData},
+    validate::validate_program,
+    Analyzer, Diagnostic, Session, Stage, SymbolFormat,
+};
+use scanner::data::Token;
 
 #[derive(Parser)]
 #[command(version = env!("CARGO_PKG_VERSION"), author = "Ernesto Ramírez (https://github.com/ErnestoRB)", about = "CLI parser for Vanilla Lang", long_about = None)]
 #[command(propagate_version = true)]
+#[command(
+    after_help = "Exit codes: 0 success, 1 other failures, 2 usage, 3 I/O, 4 lexical, 5 syntax, 6 semantic errors"
+)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
-    #[arg(short, long)]
+    #[arg(short, long, overrides_with = "no_verbose")]
     /// Turn on/off processing output
     verbose: bool,
+    /// Turn off processing output even if vanilla.toml turns it on
+    #[arg(long, overrides_with = "verbose")]
+    no_verbose: bool,
     #[arg(short, long)]
-    /// Don't write tokens to files
+    /// Write the tokens to a .lex file (same as --emit tokens)
     save: bool,
     #[arg(short, long)]
-    /// Output file to a json
+    /// Write the tree to a .json file (same as --emit ast)
     json: bool,
-    #[arg(short, long)]
+    #[arg(short, long, overrides_with = "no_analyze")]
     /// Semantic analysis
     analyze: bool,
-    /// Output symboltable to stdout
-    #[arg(long)]
+    /// Skip the semantic analysis even if vanilla.toml turns it on
+    #[arg(long, overrides_with = "analyze")]
+    no_analyze: bool,
+    /// Output symboltable to stdout (implies --analyze)
+    #[arg(long, overrides_with = "no_symbols")]
     symbols: bool,
+    /// Don't output the symbol table even if vanilla.toml turns it on
+    #[arg(long, overrides_with = "symbols")]
+    no_symbols: bool,
+    /// Format of the symbol table for --symbols and --emit symbols [default: text]
+    #[arg(long, value_enum)]
+    symbols_format: Option<SymbolsFormat>,
+    /// Artifacts to write, next to the input file unless --out-dir or -o say otherwise
+    #[arg(long, value_enum, value_delimiter = ',')]
+    emit: Vec<Emit>,
+    /// Directory for the artifacts; relative inputs keep their directory structure inside it
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+    /// Path of one artifact, e.g. `-o ast=tree.json` (implies emitting it, single file only)
+    #[arg(short, long = "output", value_name = "ARTIFACT=PATH", value_parser = parse_output)]
+    output: Vec<(Emit, PathBuf)>,
+    /// Ignore vanilla.toml
+    #[arg(long)]
+    no_config: bool,
+    #[arg(skip)]
+    config: Config,
+}
+
+impl Cli {
+    fn symbols_format(&self) -> SymbolFormat {
+        self.symbols_format.unwrap_or(SymbolsFormat::Text).into()
+    }
+
+    fn emits(&self, emit: Emit) -> bool {
+        self.emit.contains(&emit)
+            || self.output.iter().any(|(output, _)| *output == emit)
+            || (emit == Emit::Tokens && self.save)
+            || (emit == Emit::Ast && self.json)
+    }
+
+    // la tabla de símbolos y el código de tres direcciones necesitan el árbol anotado
+    fn should_analyze(&self) -> bool {
+        self.analyze || self.symbols || self.emits(Emit::Symbols) || self.emits(Emit::Tac)
+    }
+}
+
+fn parse_output(value: &str) -> Result<(Emit, PathBuf), String> {
+    let (artifact, path) = value
+        .split_once('=')
+        .ok_or_else(|| "expected ARTIFACT=PATH".to_string())?;
+    let emit = Emit::from_str(artifact, true)?;
+    if path.is_empty() {
+        return Err("the path can't be empty".to_string());
+    }
+    Ok((emit, PathBuf::from(path)))
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+enum Emit {
+    /// Token list (.lex)
+    Tokens,
+    /// Versioned JSON document with the tree, symbols and errors (.json)
+    Ast,
+    /// Symbol table in the --symbols-format (.symbols.txt, .json, .csv or .md)
+    Symbols,
+    /// Three-address code, only when there are no errors (.tac)
+    Tac,
+    /// Graphviz graph of the annotated tree (.dot)
+    AstDot,
+}
+
+impl Emit {
+    fn label(&self) -> &'static str {
+        match self {
+            Emit::Tokens => "LEX",
+            Emit::Ast => "JSON",
+            Emit::Symbols => "SYMBOLS",
+            Emit::Tac => "TAC",
+            Emit::AstDot => "DOT",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq)]
+enum SymbolsFormat {
+    Text,
+    Json,
+    Csv,
+    Md,
+}
+
+impl From<SymbolsFormat> for SymbolFormat {
+    fn from(format: SymbolsFormat) -> Self {
+        match format {
+            SymbolsFormat::Text => SymbolFormat::Text,
+            SymbolsFormat::Json => SymbolFormat::Json,
+            SymbolsFormat::Csv => SymbolFormat::Csv,
+            SymbolsFormat::Md => SymbolFormat::Markdown,
+        }
+    }
 }
 
 #[derive(Subcommand, Clone)]
 enum Commands {
     /// Parse files and print tree to stdout
     Build(BuildArgs),
+    /// Run lexing, parsing and semantic analysis without printing the tree
+    Check(CheckArgs),
+    /// Rewrite files with the canonical Vanilla style
+    Fmt(FmtArgs),
+    /// Rename a variable in a file, keeping the rest of the source untouched
+    Rename(RenameArgs),
+    /// Evaluate declarations, statements and expressions interactively
+    Repl,
+    /// Compare .cat files with their `// ERROR:` and `// STDOUT:` annotations
+    Test(TestArgs),
 }
 
 #[derive(Args, Clone)]
 struct BuildArgs {
+    /// Source files or directories (searched recursively for .cat files); `-` reads one file from
+    /// stdin. Defaults to the inputs of vanilla.toml
+    files: Vec<String>,
+    /// Read a tree saved with --json instead of a source file
+    #[arg(long, value_name = "FILE", conflicts_with = "files")]
+    from_ast: Option<String>,
+    /// Number of threads when building several files (defaults to the number of CPUs)
+    #[arg(long, value_name = "N")]
+    jobs: Option<usize>,
+    /// Check the files again every time they change, showing only the new diagnostics
+    #[arg(long, conflicts_with = "from_ast")]
+    watch: bool,
+    /// With --watch, also run the programs without errors and show their output
+    #[arg(long, requires = "watch")]
+    run: bool,
+}
+
+#[derive(Args, Clone)]
+struct CheckArgs {
+    /// Source files or directories (searched recursively for .cat files); `-` reads one file from
+    /// stdin. Defaults to the inputs of vanilla.toml
+    files: Vec<String>,
+    /// Number of threads when checking several files (defaults to the number of CPUs)
+    #[arg(long, value_name = "N")]
+    jobs: Option<usize>,
+}
+
+#[derive(Args, Clone)]
+struct TestArgs {
+    /// Test files or directories (searched recursively for .cat files)
+    #[arg(required = true)]
+    paths: Vec<String>,
+    /// Rewrite the annotations with the current diagnostics and output
+    #[arg(long)]
+    bless: bool,
+}
+
+#[derive(Args, Clone)]
+struct FmtArgs {
+    files: Vec<String>,
+    /// Don't write the files, fail if any of them is not formatted
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Args, Clone)]
+struct RenameArgs {
     file: String,
+    /// Current name of the variable. When several blocks declare it, use its key in the symbol
+    /// table (`x#1`, `x#2`, ...) to pick the declaration
+    old: String,
+    /// New name of the variable
+    new: String,
 }
 
-fn main() {
-    let mut cli = Cli::parse();
-    cli.analyze = true;
+fn format_files(args: &FmtArgs) -> bool {
+    let mut ok = true;
+    for file in args.files.iter() {
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("ERROR: Could not read {}: {}", file, e);
+                ok = false;
+                continue;
+            }
+        };
+        let (tokens, errors) = tokenize_str(&source);
+        if !errors.is_empty() {
+            for err in errors.iter() {
+                eprintln!(
+                    "ERROR: ({}, [({}, {})-({},{})]): {} ",
+                    file, err.start.col, err.start.lin, err.end.col, err.end.lin, err.message,
+                );
+            }
+            ok = false;
+            continue;
+        }
+        match format_source(&source, tokens) {
+            Ok(formatted) => {
+                if formatted == source {
+                    continue;
+                }
+                if args.check {
+                    println!("{} is not formatted", file);
+                    ok = false;
+                } else if let Err(e) = fs::write(file, formatted) {
+                    eprintln!("ERROR: Could not write to {}: {}", file, e);
+                    ok = false;
+                } else {
+                    println!("Formatted {}", file);
+                }
+            }
+            Err(errors) => {
+                for err in errors {
+                    eprintln!("ERROR: Parsing error in {}: {:?}", file, err);
+                }
+                ok = false;
+            }
+        }
+    }
+    ok
+}
 
-    match &cli.command {
-        Commands::Build(args) => {
-            let file = args.file.clone();
-            println!("{}", file);
-            let tokenization = tokenize_file(&file);
-            match tokenization {
-                Ok(res) => {
-                    if cli.verbose {
-                        println!(
-                            "[VERBOSE] Tokenizing {}: {} Tokens, {} Errors",
-                            file,
-                            res.0.len(),
-                            res.1.len()
-                        );
-                    }
-                    if cli.save {
-                        if let Some(filename) = Path::new(&file).file_name() {
-                            let output_file = Path::new(&file)
-                                .parent()
-                                .unwrap_or(Path::new("."))
-                                .join(filename.to_str().unwrap().to_owned() + ".lex");
-                            if cli.verbose {
-                                println!("[VERBOSE] Trying to save to {:?}", output_file.to_str());
-                            }
+fn rename_file(args: &RenameArgs) -> bool {
+    let source = match fs::read_to_string(&args.file) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("ERROR: Could not read {}: {}", args.file, e);
+            return false;
+        }
+    };
+    let (tokens, errors) = tokenize_str(&source);
+    if !errors.is_empty() {
+        for err in errors.iter() {
+            eprintln!(
+                "ERROR: ({}, [({}, {})-({},{})]): {} ",
+                args.file, err.start.col, err.start.lin, err.end.col, err.end.lin, err.message,
+            );
+        }
+        return false;
+    }
+    match rename_symbol(&source, tokens, &args.old, &args.new) {
+        Ok(renamed) => {
+            if let Err(e) = fs::write(&args.file, renamed) {
+                eprintln!("ERROR: Could not write to {}: {}", args.file, e);
+                return false;
+            }
+            println!("Renamed {} to {} in {}", args.old, args.new, args.file);
+            true
+        }
+        Err(e) => {
+            eprintln!("ERROR: {}", e.message);
+            false
+        }
+    }
+}
 
-                            if let Ok(mut file_handle) = fs::File::create(output_file.clone()) {
-                                for token in res.0.iter() {
-                                    if let Err(_) = file_handle.write_fmt(format_args!(
-                                        "{:?}, {}\n",
-                                        token.token_type, token.lexemme
-                                    )) {
-                                        eprintln!(
-                                            "ERROR: Could not write to {}",
-                                            output_file.to_str().unwrap()
-                                        );
-                                    }
-                                }
-                            } else {
-                                eprintln!("ERROR: Could not create file {}", file);
-                            }
-                        } else {
-                            eprintln!(
-                                "ERROR: Could not save file {} due to invalid filename",
-                                file
-                            );
-                        }
-                    }
-                    if cli.verbose {
-                        for err in res.1.iter() {
-                            eprintln!(
-                                "ERROR: ({}, [({}, {})-({},{})]): {} ",
-                                file,
-                                err.start.col,
-                                err.start.lin,
-                                err.end.col,
-                                err.end.lin,
-                                err.message,
-                            );
-                        }
-                    }
-                    let (root_op, errors) = parse(res.0);
-                    if let Some(mut root) = root_op {
-                        //hubo arbol
-                        if let Some(filename) = Path::new(&file).file_name() {
-                            if cli.json {
-                                let json_file = Path::new(&file)
-                                    .parent()
-                                    .unwrap_or(Path::new("."))
-                                    .join(filename.to_str().unwrap().to_owned() + ".json");
-                                println!("[JSON] Trying to save to {:?}", json_file.to_str());
-                                let json = serde_json::to_string_pretty(&root);
-                                match json {
-                                    Ok(str) => {
-                                        if let Ok(mut file_handle) =
-                                            fs::File::create(json_file.clone())
-                                        {
-                                            if let Err(_) =
-                                                file_handle.write_fmt(format_args!("{}", str))
-                                            {
-                                                eprintln!(
-                                                    "ERROR: Could not write to {}",
-                                                    json_file.to_str().unwrap()
-                                                );
-                                            }
-                                        } else {
-                                            eprintln!(
-                                                "ERROR: Could not create file {}",
-                                                json_file.to_string_lossy()
-                                            );
-                                        }
-                                    }
-                                    Err(_) => todo!(),
-                                }
-                            } else {
-                                eprintln!(
-                                    "ERROR: Could not save file {} due to invalid filename",
-                                    file
-                                );
-                            }
-                        }
-                        root.print(); // imprimir a stdout
-
-                        if cli.analyze {
-                            let analyzer = Analyzer::new();
-                            let (errors, symbol_table) = analyzer.analyze(&mut root); // Hacer mutable la tabla de símbolos
-                            println!("Arbol con anotaciones:");
-                            root.print(); // imprimir a stdout
-                            if !errors.is_empty() {
-                                eprintln!("Errores al analizar semánticamente:");
-                                for error in errors {
-                                    eprintln!(
-                                        "ERROR: {} en la posición {:?}",
-                                        error.message, error.cursor
-                                    );
-                                }
-                            } else {
-                            }
-                            if cli.symbols {
-                                print_sym_table(&symbol_table);
+const REPL_HELP: &str = "Enter declarations, statements or expressions. Commands:
+  :symbols  show declared variables and arrays with their values
+  :ast      show the tree of the accepted input
+  :reset    forget every variable
+  :quit     exit";
+
+fn print_repl_error(error: ReplError) {
+    match error {
+        ReplError::Lex(errors) => {
+            for err in errors {
+                eprintln!("ERROR: {}", err);
+            }
+        }
+        ReplError::Parse(errors) => {
+            for err in errors {
+                eprintln!("ERROR: Parsing error: {:?}", err);
+            }
+        }
+        ReplError::Analyze(errors) => {
+            for error in errors {
+                eprintln!("ERROR: {} en la posición {:?}", error.message, error.cursor);
+            }
+        }
+        ReplError::Runtime(error) => eprintln!("ERROR: {}", error.message),
+    }
+}
+
+fn run_repl() {
+    let mut repl = Repl::new();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut buffer = String::new();
+    println!("Vanilla REPL. Type :help for commands");
+    loop {
+        print!("{}", if buffer.is_empty() { ">> " } else { ".. " });
+        let _ = stdout.flush();
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if buffer.is_empty() {
+            match line.trim() {
+                "" => continue,
+                ":quit" | ":q" => break,
+                ":help" => {
+                    println!("{}", REPL_HELP);
+                    continue;
+                }
+                ":symbols" => {
+                    for symbol in repl.symbols() {
+                        let values: Vec<String> =
+                            symbol.values.iter().map(value_to_string).collect();
+                        match symbol.size {
+                            Some(size) => println!(
+                                "{}: {:?}[{}] = [{}]",
+                                symbol.name,
+                                symbol.typ,
+                                size,
+                                values.join(", ")
+                            ),
+                            None => {
+                                println!("{}: {:?} = {}", symbol.name, symbol.typ, values.concat())
                             }
                         }
                     }
-                    if !errors.is_empty() {
-                        for err in errors {
-                            eprintln!("ERROR: Parsing error: {:?}", err);
-                        }
-                    }
+                    continue;
+                }
+                ":ast" => {
+                    repl.program().print();
+                    continue;
+                }
+                ":reset" => {
+                    repl.reset();
+                    println!("State cleared");
+                    continue;
+                }
+                command if command.starts_with(':') => {
+                    eprintln!("ERROR: Unknown command {}", command);
+                    continue;
                 }
+                _ => {}
+            }
+        }
+        buffer.push_str(&line);
+        if !Repl::is_complete(&buffer) {
+            continue;
+        }
+        let input = std::mem::take(&mut buffer);
+        match repl.eval(&input, &mut stdin.lock(), &mut stdout) {
+            Ok(ReplOutput::Value(typ, value)) => {
+                println!("{}: {:?}", value_to_string(&value), typ)
+            }
+            Ok(ReplOutput::Executed) => {}
+            Err(error) => print_repl_error(error),
+        }
+    }
+}
+
+// Análisis semántico y artefactos que dependen del árbol anotado
+fn analyze_program(
+    cli: &Cli,
+    file: &str,
+    root: &mut Program,
+    lex_ok: bool,
+    parse_errors: &[ParseError],
+) -> bool {
+    let (analyze_errors, symbol_table) = if cli.should_analyze() {
+        let analyzer = Analyzer::new().with_config(&cli.config);
+        let (errors, warnings, symbol_table) = analyzer.analyze_with_warnings(root); // Hacer mutable la tabla de símbolos
+        println!("Arbol con anotaciones:");
+        root.print(); // imprimir a stdout
+        if !errors.is_empty() {
+            eprintln!("Errores al analizar semánticamente:");
+            for error in errors.iter() {
+                eprintln!("ERROR: {} en la posición {:?}", error.message, error.cursor);
+            }
+        }
+        for warning in warnings.iter() {
+            eprintln!(
+                "WARNING: {} en la posición {:?}",
+                warning.message, warning.cursor
+            );
+        }
+        if cli.symbols {
+            print!(
+                "{}",
+                format_symbol_table(&symbol_table, cli.symbols_format())
+            );
+        }
+        (errors, symbol_table)
+    } else {
+        (vec![], HashMap::new())
+    };
+    let errors = StageErrors {
+        lex_ok,
+        parse: parse_errors,
+        analyze: &analyze_errors,
+    };
+    write_artifacts(cli, &mut Log::default(), file, root, &symbol_table, errors);
+    analyze_errors.is_empty()
+}
+
+// Ruta de un artefacto: la de `-o`, o el nombre del archivo con la extensión del artefacto,
+// junto al archivo de entrada o dentro de `--out-dir`
+fn artifact_path(cli: &Cli, file: &str, emit: Emit) -> PathBuf {
+    if let Some((_, path)) = cli.output.iter().find(|(output, _)| *output == emit) {
+        return path.clone();
+    }
+    let extension = match emit {
+        Emit::Tokens => "lex".to_string(),
+        Emit::Ast => "json".to_string(),
+        Emit::Symbols => {
+            let format: SymbolFormat = cli.symbols_format();
+            format!("symbols.{}", format.extension())
+        }
+        Emit::Tac => "tac".to_string(),
+        Emit::AstDot => "dot".to_string(),
+    };
+    let input = Path::new(file);
+    let name = format!(
+        "{}.{}",
+        input.file_name().unwrap_or_default().to_string_lossy(),
+        extension
+    );
+    match &cli.out_dir {
+        // las rutas relativas conservan sus directorios para que no choquen los nombres
+        Some(out_dir)
+            if input.is_relative()
+                && input
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_))) =>
+        {
+            out_dir.join(input.with_file_name(name))
+        }
+        Some(out_dir) => out_dir.join(name),
+        None => input.with_file_name(name),
+    }
+}
+
+fn write_artifact(cli: &Cli, log: &mut Log, file: &str, emit: Emit, contents: &str) {
+    let path = artifact_path(cli, file, emit);
+    log.info(format!("[{}] Trying to save to {:?}", emit.label(), path));
+    let result = match path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+    .and_then(|_| fs::write(&path, contents));
+    if let Err(e) = result {
+        log.error(format!(
+            "ERROR: Could not write to {}: {}",
+            path.display(),
+            e
+        ));
+    }
+}
+
+// Errores de las etapas que ya corrieron; el JSON los incluye y el TAC solo se genera sin ellos
+struct StageErrors<'a> {
+    lex_ok: bool,
+    parse: &'a [ParseError],
+    analyze: &'a [AnalyzeError],
+}
+
+// Artefactos que dependen del árbol: JSON, tabla de símbolos, código de tres direcciones y grafo
+fn write_artifacts(
+    cli: &Cli,
+    log: &mut Log,
+    file: &str,
+    root: &Program,
+    symbol_table: &HashMap<String, SymbolData>,
+    errors: StageErrors,
+) {
+    if cli.emits(Emit::Ast) {
+        let document = AstDocument::new(
+            root.clone(),
+            symbol_table,
+            errors.parse.to_vec(),
+            errors.analyze.to_vec(),
+        );
+        match serde_json::to_string_pretty(&document) {
+            Ok(json) => write_artifact(cli, log, file, Emit::Ast, &json),
+            Err(e) => log.error(format!("ERROR: Could not serialize the tree: {}", e)),
+        }
+    }
+    if cli.emits(Emit::Symbols) {
+        let table = format_symbol_table(symbol_table, cli.symbols_format());
+        write_artifact(cli, log, file, Emit::Symbols, &table);
+    }
+    if cli.emits(Emit::Tac) {
+        if errors.lex_ok && errors.parse.is_empty() && errors.analyze.is_empty() {
+            let code = tac_to_string(&generate_tac(root));
+            write_artifact(cli, log, file, Emit::Tac, &code);
+        } else {
+            log.error(format!(
+                "ERROR: Not generating TAC for {}: it has errors",
+                file
+            ));
+        }
+    }
+    if cli.emits(Emit::AstDot) {
+        write_artifact(cli, log, file, Emit::AstDot, &program_to_dot(root));
+    }
+}
+
+// Carga un árbol guardado con --json y lo pasa por el análisis
+fn build_from_ast(cli: &Cli, file: &str) -> Result<(), Stage> {
+    let json = match fs::read_to_string(file) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("ERROR: Could not read {}: {}", file, e);
+            return Err(Stage::Io);
+        }
+    };
+    // un árbol inválido cuenta como error de sintaxis
+    let mut root = match program_from_json(&json) {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("ERROR: {} is not a valid tree: {}", file, e);
+            return Err(Stage::Parse);
+        }
+    };
+    if let Err(errors) = validate_program(&root) {
+        for error in errors {
+            eprintln!(
+                "ERROR: Invalid tree: {} (node {}, position {:?})",
+                error.message, error.id.0, error.cursor
+            );
+        }
+        return Err(Stage::Parse);
+    }
+    root.print(); // imprimir a stdout
+    if analyze_program(cli, file, &mut root, true, &[]) {
+        Ok(())
+    } else {
+        Err(Stage::Analyze)
+    }
+}
+
+// Guarda los tokens, uno por línea
+fn save_tokens(cli: &Cli, log: &mut Log, file: &str, tokens: &[Token]) {
+    let contents: String = tokens
+        .iter()
+        .map(|token| format!("{:?}, {}\n", token.token_type, token.lexemme))
+        .collect();
+    write_artifact(cli, log, file, Emit::Tokens, &contents);
+}
+
+// Compila un solo archivo mostrando el árbol y los errores completos
+fn build_file(cli: &Cli, file: &str) -> Result<(), Stage> {
+    println!("{}", file);
+    // `-` lee el código de stdin
+    let tokenization = if file == "-" {
+        let mut source = String::new();
+        io::stdin()
+            .read_to_string(&mut source)
+            .map(|_| tokenize_str(&source))
+            .map_err(|e| e.to_string())
+    } else {
+        tokenize_file(file).map_err(|e| e.to_string())
+    };
+    // los diagnósticos de stdin usan `STDIN_NAME` y sus artefactos se guardan como `stdin.*` en
+    // el directorio actual
+    let file = if file == "-" { STDIN_NAME } else { file };
+    match tokenization {
+        Ok(res) => {
+            if cli.verbose {
+                println!(
+                    "[VERBOSE] Tokenizing {}: {} Tokens, {} Errors",
+                    file,
+                    res.0.len(),
+                    res.1.len()
+                );
+            }
+            if cli.emits(Emit::Tokens) {
+                save_tokens(cli, &mut Log::default(), file, &res.0);
+            }
+            for err in res.1.iter() {
+                let diagnostic = Diagnostic {
+                    file: file.to_string(),
+                    stage: Stage::Lex,
+                    message: err.message.clone(),
+                    cursor: Some(err.start.clone()),
+                };
+                eprintln!("ERROR: {}", diagnostic);
+            }
+            let lex_ok = res.1.is_empty();
+            let (root_op, errors) = parse(res.0);
+            let mut analyze_ok = true;
+            if let Some(mut root) = root_op {
+                //hubo arbol
+                root.print(); // imprimir a stdout
+                analyze_ok = analyze_program(cli, file, &mut root, lex_ok, &errors);
+            }
+            let parse_ok = errors.is_empty();
+            for err in errors {
+                eprintln!("ERROR: Parsing error: {:?}", err);
+            }
+            if !lex_ok {
+                Err(Stage::Lex)
+            } else if !parse_ok {
+                Err(Stage::Parse)
+            } else if !analyze_ok {
+                Err(Stage::Analyze)
+            } else {
+                Ok(())
+            }
+        }
+        Err(e) => {
+            eprintln!("ERROR: Could not generate output for {}: {}", file, e);
+            Err(Stage::Io)
+        }
+    }
+}
+
+// Archivos de las rutas dadas; los directorios se recorren buscando `*.cat`
+fn collect_sources(paths: &[String]) -> Vec<String> {
+    let mut files = vec![];
+    for path in paths {
+        if Path::new(path).is_dir() {
+            collect_dir(Path::new(path), &mut files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files
+}
+
+fn collect_dir(dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        // la sesión reporta el error al intentar leerlo
+        files.push(dir.to_string_lossy().to_string());
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            collect_dir(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "cat") {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+}
+
+// Códigos de salida por etapa; el 2 lo usa clap para los errores de uso
+const EXIT_IO: i32 = 3;
+const EXIT_LEX: i32 = 4;
+const EXIT_PARSE: i32 = 5;
+const EXIT_ANALYZE: i32 = 6;
+
+fn exit_code(stage: Stage) -> i32 {
+    match stage {
+        Stage::Io => EXIT_IO,
+        Stage::Lex => EXIT_LEX,
+        Stage::Parse => EXIT_PARSE,
+        Stage::Analyze => EXIT_ANALYZE,
+    }
+}
+
+// Mensajes de un archivo. Al compilar varios a la vez se guardan y se imprimen juntos al final,
+// para que no se mezclen los de distintos hilos; sin `buffered` se imprimen al momento
+#[derive(Default)]
+struct Log {
+    buffered: bool,
+    // cada línea con si va a stderr
+    lines: Vec<(bool, String)>,
+}
+
+impl Log {
+    fn buffered() -> Self {
+        Log {
+            buffered: true,
+            lines: vec![],
+        }
+    }
+
+    fn info(&mut self, line: String) {
+        if self.buffered {
+            self.lines.push((false, line));
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    fn error(&mut self, line: String) {
+        if self.buffered {
+            self.lines.push((true, line));
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+
+    fn print(&self) {
+        for (is_error, line) in self.lines.iter() {
+            if *is_error {
+                eprintln!("{}", line);
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+// Resultado de un archivo cuando se compilan varios a la vez
+struct FileSummary {
+    file: String,
+    tokens: usize,
+    diagnostics: Vec<Diagnostic>,
+    warnings: Vec<Diagnostic>,
+    log: Log,
+}
+
+impl FileSummary {
+    fn count(&self, stage: Stage) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.stage == stage)
+            .count()
+    }
+
+    // La etapa más temprana con errores es la que decide el código de salida
+    fn failed_stage(&self) -> Option<Stage> {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.stage)
+            .min()
+    }
+}
+
+fn summarize_file(cli: &Cli, file: &str) -> FileSummary {
+    let mut session = match open_session(cli, file) {
+        Ok(session) => session,
+        Err(summary) => return summary,
+    };
+    // igual que con un solo archivo, los artefactos de stdin se guardan como `stdin.*`
+    let file = if file == "-" { STDIN_NAME } else { file };
+    let mut log = Log::buffered();
+    let program = if cli.should_analyze() {
+        session.analyzed_program().cloned()
+    } else {
+        session.program().cloned()
+    };
+    if cli.emits(Emit::Tokens) && !session.tokens().is_empty() {
+        save_tokens(cli, &mut log, file, session.tokens());
+    }
+    if let Some(program) = program {
+        let parse_errors = session.parse_errors().to_vec();
+        let (analyze_errors, symbols) = if cli.should_analyze() {
+            (
+                session.analyze_errors().to_vec(),
+                session.symbols().cloned().unwrap_or_default(),
+            )
+        } else {
+            (vec![], HashMap::new())
+        };
+        let lex_ok = session
+            .diagnostics()
+            .iter()
+            .all(|diagnostic| diagnostic.stage != Stage::Lex);
+        if cli.symbols {
+            let table = format_symbol_table(&symbols, cli.symbols_format());
+            log.info(format!("{}:\n{}", file, table.trim_end()));
+        }
+        let errors = StageErrors {
+            lex_ok,
+            parse: &parse_errors,
+            analyze: &analyze_errors,
+        };
+        write_artifacts(cli, &mut log, file, &program, &symbols, errors);
+    }
+    FileSummary {
+        file: session.name().to_string(),
+        tokens: session.tokens().len(),
+        diagnostics: session.diagnostics().to_vec(),
+        warnings: session.warnings().to_vec(),
+        log,
+    }
+}
+
+// `-` lee el código de stdin. Si no se puede leer, el error queda en el resumen del archivo
+fn open_session(cli: &Cli, file: &str) -> Result<Session, FileSummary> {
+    let session = if file == "-" {
+        Session::from_stdin()
+    } else {
+        Ok(Session::from_path(file))
+    };
+    match session {
+        Ok(session) => Ok(session.with_config(&cli.config)),
+        Err(e) => Err(FileSummary {
+            file: STDIN_NAME.to_string(),
+            tokens: 0,
+            diagnostics: vec![Diagnostic {
+                file: STDIN_NAME.to_string(),
+                stage: Stage::Io,
+                message: e.to_string(),
+                cursor: None,
+            }],
+            warnings: vec![],
+            log: Log::default(),
+        }),
+    }
+}
+
+fn check_file(cli: &Cli, file: &str) -> FileSummary {
+    let mut session = match open_session(cli, file) {
+        Ok(session) => session,
+        Err(summary) => return summary,
+    };
+    session.check();
+    FileSummary {
+        file: session.name().to_string(),
+        tokens: session.tokens().len(),
+        diagnostics: session.diagnostics().to_vec(),
+        warnings: session.warnings().to_vec(),
+        log: Log::default(),
+    }
+}
+
+// Procesa los archivos en varios hilos; los resultados quedan en el orden dado
+fn run_files(
+    files: &[String],
+    jobs: Option<usize>,
+    process: impl Fn(&str) -> FileSummary + Sync,
+) -> Vec<FileSummary> {
+    let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let next = AtomicUsize::new(0);
+    let summaries: Mutex<Vec<Option<FileSummary>>> =
+        Mutex::new(files.iter().map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, files.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else {
+                    break;
+                };
+                let summary = process(file);
+                summaries.lock().unwrap()[index] = Some(summary);
+            });
+        }
+    });
+    summaries
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+// Compila los archivos e imprime un resumen por archivo
+fn build_files(cli: &Cli, files: &[String], jobs: Option<usize>) -> Result<(), Stage> {
+    let summaries = run_files(files, jobs, |file| summarize_file(cli, file));
+    let mut failed = 0;
+    for summary in summaries.iter() {
+        summary.log.print();
+        let ok = summary.diagnostics.is_empty();
+        if !ok {
+            failed += 1;
+        }
+        println!(
+            "{:<4} {}: {} tokens, {} lexical, {} syntax, {} semantic errors",
+            if ok { "ok" } else { "FAIL" },
+            summary.file,
+            summary.tokens,
+            summary.count(Stage::Lex),
+            summary.count(Stage::Parse),
+            summary.count(Stage::Analyze)
+        );
+        for diagnostic in summary.diagnostics.iter() {
+            eprintln!("ERROR: {}", diagnostic);
+        }
+        for warning in summary.warnings.iter() {
+            eprintln!("WARNING: {}", warning);
+        }
+    }
+    println!(
+        "{} files, {} ok, {} failed",
+        files.len(),
+        files.len() - failed,
+        failed
+    );
+    match summaries.iter().filter_map(FileSummary::failed_stage).min() {
+        Some(stage) => Err(stage),
+        None => Ok(()),
+    }
+}
+
+fn build(cli: &Cli, args: &BuildArgs) -> Result<(), Stage> {
+    let files = input_files(cli, &args.files);
+    if args.watch {
+        watch(cli, &files, args.run);
+    }
+    // un solo archivo conserva la salida detallada
+    if let [file] = files.as_slice() {
+        if !Path::new(file).is_dir() {
+            return build_file(cli, file);
+        }
+    }
+    if !cli.output.is_empty() {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "-o can only be used when building a single file, use --out-dir instead",
+            )
+            .exit();
+    }
+    let files = collect_sources(&files);
+    if files.is_empty() {
+        eprintln!("ERROR: No .cat files found");
+        return Err(Stage::Io);
+    }
+    build_files(cli, &files, args.jobs)
+}
+
+// Corre el scanner, el parser y el análisis sin imprimir el árbol; solo se muestran los errores
+fn check(cli: &Cli, args: &CheckArgs) -> Result<(), Stage> {
+    let files = collect_sources(&input_files(cli, &args.files));
+    if files.is_empty() {
+        eprintln!("ERROR: No .cat files found");
+        return Err(Stage::Io);
+    }
+    let summaries = run_files(&files, args.jobs, |file| check_file(cli, file));
+    for summary in summaries.iter() {
+        for diagnostic in summary.diagnostics.iter() {
+            eprintln!("ERROR: {}", diagnostic);
+        }
+        for warning in summary.warnings.iter() {
+            eprintln!("WARNING: {}", warning);
+        }
+    }
+    match summaries.iter().filter_map(FileSummary::failed_stage).min() {
+        Some(stage) => Err(stage),
+        None => Ok(()),
+    }
+}
+
+// Corre las pruebas de archivos anotados; con --bless actualiza las anotaciones
+fn run_tests(args: &TestArgs) -> bool {
+    let files = collect_sources(&args.paths);
+    let mut failed = 0;
+    for file in files.iter() {
+        if args.bless {
+            match bless_golden(Path::new(file)) {
+                Ok(true) => println!("blessed {}", file),
+                Ok(false) => {}
                 Err(e) => {
-                    eprintln!("ERROR: Could not generate output for {}: {}", file, e)
+                    failed += 1;
+                    eprintln!("ERROR: Could not bless {}: {}", file, e);
                 }
             }
+            continue;
+        }
+        let result = run_golden(Path::new(file));
+        if result.passed() {
+            println!("ok   {}", file);
+        } else {
+            failed += 1;
+            println!("FAIL {}", file);
+            for failure in result.failures.iter() {
+                println!("  {}", failure);
+            }
+        }
+    }
+    if !args.bless {
+        println!(
+            "{} tests, {} passed, {} failed",
+            files.len(),
+            files.len() - failed,
+            failed
+        );
+    }
+    failed == 0
+}
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+// Fecha de modificación de cada archivo, para saber cuándo volver a compilar
+fn modification_times(files: &[String]) -> Vec<(String, Option<SystemTime>)> {
+    files
+        .iter()
+        .map(|file| {
+            let modified = fs::metadata(file).and_then(|meta| meta.modified()).ok();
+            (file.clone(), modified)
+        })
+        .collect()
+}
+
+fn watch_round(cli: &Cli, files: &[String], run: bool) {
+    // limpia la pantalla y regresa el cursor al inicio
+    print!("\x1b[2J\x1b[H");
+    println!(
+        "Watching {} files every {} ms (Ctrl+C to stop)",
+        files.len(),
+        WATCH_INTERVAL.as_millis()
+    );
+    for file in files.iter() {
+        let mut session = Session::from_path(file).with_config(&cli.config);
+        let diagnostics = session.check().to_vec();
+        if !diagnostics.is_empty() {
+            println!("FAIL {}", file);
+            for diagnostic in diagnostics.iter() {
+                println!("  {}", diagnostic);
+            }
+            continue;
+        }
+        println!("ok   {}", file);
+        for warning in session.warnings() {
+            println!("  warning: {}", warning);
+        }
+        if !run {
+            continue;
+        }
+        if let Some(program) = session.analyzed_program() {
+            // los programas corren sin entrada: un `stdin` termina con error
+            let mut output = vec![];
+            let result = Interpreter::new().with_limit(MAX_INSTRUCTIONS).run(
+                program,
+                &mut io::empty(),
+                &mut output,
+            );
+            for line in String::from_utf8_lossy(&output).lines() {
+                println!("  | {}", line);
+            }
+            if let Err(error) = result {
+                println!("  runtime error: {} at {:?}", error.message, error.cursor);
+            }
+        }
+    }
+    let _ = io::stdout().flush();
+}
+
+// Revisa las rutas cada `WATCH_INTERVAL` y vuelve a compilar cuando algún archivo cambia, se
+// agrega o se borra. No termina hasta que se interrumpe el proceso
+fn watch(cli: &Cli, paths: &[String], run: bool) -> ! {
+    let mut previous = None;
+    loop {
+        let files = collect_sources(paths);
+        let times = modification_times(&files);
+        if previous.as_ref() != Some(&times) {
+            watch_round(cli, &files, run);
+            previous = Some(times);
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+// Sin archivos en la línea de comandos se usan los `inputs` de vanilla.toml
+fn input_files(cli: &Cli, files: &[String]) -> Vec<String> {
+    if !files.is_empty() {
+        return files.to_vec();
+    }
+    let inputs = match cli.config.input_files() {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if inputs.is_empty() {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "no input files given; pass them or list `inputs` in vanilla.toml",
+            )
+            .exit();
+    }
+    // se muestran relativos al directorio actual cuando se puede
+    let cwd = env::current_dir().unwrap_or_default();
+    inputs
+        .into_iter()
+        .map(|input| match Path::new(&input).strip_prefix(&cwd) {
+            Ok(relative) => relative.to_string_lossy().to_string(),
+            Err(_) => input,
+        })
+        .collect()
+}
+
+// Completa las opciones con el vanilla.toml más cercano; la línea de comandos tiene prioridad
+fn apply_config(cli: &mut Cli) {
+    if cli.no_config {
+        return;
+    }
+    let Some(path) = env::current_dir()
+        .ok()
+        .and_then(|dir| Config::discover(&dir))
+    else {
+        return;
+    };
+    let invalid = |message: String| -> ! {
+        eprintln!("ERROR: Invalid {}: {}", path.display(), message);
+        std::process::exit(1);
+    };
+    let config = Config::load(&path).unwrap_or_else(|e| invalid(e));
+    if (cli.verbose || config.build.verbose) && !cli.no_verbose {
+        println!("[VERBOSE] Using {}", path.display());
+    }
+    // las banderas `--no-*` apagan lo que encienda el archivo
+    cli.verbose = (cli.verbose || config.build.verbose) && !cli.no_verbose;
+    cli.analyze = (cli.analyze || config.build.analyze) && !cli.no_analyze;
+    cli.symbols = (cli.symbols || config.build.symbols) && !cli.no_symbols;
+    if cli.symbols_format.is_none() {
+        if let Some(format) = &config.build.symbols_format {
+            cli.symbols_format = Some(
+                SymbolsFormat::from_str(format, true)
+                    .unwrap_or_else(|_| invalid(format!("unknown symbols_format {}", format))),
+            );
+        }
+    }
+    if cli.emit.is_empty() {
+        for emit in config.build.emit.iter() {
+            cli.emit.push(
+                Emit::from_str(emit, true)
+                    .unwrap_or_else(|_| invalid(format!("unknown emit target {}", emit))),
+            );
+        }
+    }
+    if cli.out_dir.is_none() {
+        cli.out_dir = config.out_dir();
+    }
+    cli.config = config;
+}
+
+fn main() {
+    let mut cli = Cli::parse();
+    apply_config(&mut cli);
+
+    match &cli.command {
+        Commands::Build(BuildArgs {
+            from_ast: Some(ast_file),
+            ..
+        }) => {
+            if let Err(stage) = build_from_ast(&cli, ast_file) {
+                std::process::exit(exit_code(stage));
+            }
+        }
+        Commands::Build(args) => {
+            if let Err(stage) = build(&cli, args) {
+                std::process::exit(exit_code(stage));
+            }
+        }
+        Commands::Check(args) => {
+            if let Err(stage) = check(&cli, args) {
+                std::process::exit(exit_code(stage));
+            }
+        }
+        Commands::Fmt(args) => {
+            if !format_files(args) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Rename(args) => {
+            if !rename_file(args) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Repl => run_repl(),
+        Commands::Test(args) => {
+            if !run_tests(args) {
+                std::process::exit(1);
+            }
         }
     }
 }
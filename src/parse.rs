@@ -1,20 +1,47 @@
+pub mod node_index;
 pub mod structures;
 pub mod utils;
+pub mod validate;
+pub mod visit;
 
 use scanner::data::{Cursor, Token, TokenType};
 use std::collections::VecDeque;
-use structures::{DeclKind, ExpKind, ExpType, Node, ParseError, StmtKind, TreeNode};
-use uuid::Uuid;
+use structures::{
+    DeclKind, ExpKind, ExpType, Node, NodeId, ParseError, Program, StmtKind, TreeNode,
+};
+
+// El scanner no tiene tokens para el tipo booleano: `boolean`, `true` y `false` llegan como ID y el
+// parser los reconoce por su lexema
+pub const BOOLEAN: &str = "boolean";
+pub const TRUE: &str = "true";
+pub const FALSE: &str = "false";
+// Lo mismo pasa con `void` (funciones sin valor de regreso) y `return`
+pub const VOID: &str = "void";
+pub const RETURN: &str = "return";
 
 struct Parser {
     tokens: VecDeque<Token>,
     errors: Vec<ParseError>,
     current_cursor: Option<Cursor>,
+    next_id: u32,
 }
 
-pub fn parse(tokens: Vec<Token>) -> (Option<TreeNode>, Vec<ParseError>) {
+pub fn parse(tokens: Vec<Token>) -> (Option<Program>, Vec<ParseError>) {
     let parser = Parser::new(VecDeque::from(tokens));
-    return parser.parse();
+    parser.parse()
+}
+
+// Parsea una expresión suelta, con un `;` opcional al final
+pub fn parse_expression(mut tokens: Vec<Token>) -> (Option<TreeNode>, Vec<ParseError>) {
+    // las reglas de expresión esperan que siempre haya un token después, como en una sentencia
+    if let Some(last) = tokens.last().filter(|t| t.token_type != TokenType::SCOL) {
+        let mut end = last.clone();
+        end.token_type = TokenType::SCOL;
+        end.lexemme = ";".to_string();
+        tokens.push(end);
+    }
+    let parser = Parser::new(VecDeque::from(tokens));
+    parser.parse_expression()
 }
 
 impl Parser {
@@ -24,9 +51,17 @@ impl Parser {
             errors: vec![],
             tokens,
             current_cursor,
+            next_id: 0,
         }
     }
 
+    // Los ids se asignan en orden de creación, así el mismo programa siempre genera el mismo árbol
+    fn new_id(&mut self) -> NodeId {
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
     fn _match(&mut self, token: TokenType, handle_error: bool) -> bool {
         let current_token = self.get_current_token().cloned();
         match current_token {
@@ -61,6 +96,36 @@ impl Parser {
         true
     }
 
+    // Consume el corchete `token` (LBRACKET o RBRACKET) o reporta que hacía falta
+    fn _match_bracket(&mut self, token: TokenType) -> bool {
+        let current_token = self.get_current_token().cloned();
+        match current_token {
+            Some(current) if current.token_type == token => {
+                self.get_next_token();
+                true
+            }
+            current_token => {
+                let lexemme = if token == TokenType::LBRACKET {
+                    "["
+                } else {
+                    "]"
+                };
+                self.errors.push(ParseError {
+                    message: format!("Se esperaba '{}'", lexemme),
+                    expected_token_type: Some(vec![token]),
+                    current_token,
+                });
+                false
+            }
+        }
+    }
+
+    fn is_left_bracket(&self) -> bool {
+        self.tokens
+            .front()
+            .is_some_and(|token| token.token_type == TokenType::LBRACKET)
+    }
+
     fn get_current_token(&mut self) -> Option<&Token> {
         self.tokens.front()
     }
@@ -70,6 +135,53 @@ impl Parser {
         self.tokens.pop_front()
     }
 
+    // `boolean x` es una declaración; `boolean = 1` sigue siendo una asignación
+    fn is_boolean_declaration(&self) -> bool {
+        matches!(
+            (self.tokens.front(), self.tokens.get(1)),
+            (Some(first), Some(second))
+                if first.token_type == TokenType::ID
+                    && first.lexemme == BOOLEAN
+                    && second.token_type == TokenType::ID
+        )
+    }
+
+    // Tipo de variable, parámetro o valor de regreso con el que empieza una declaración
+    fn is_type(&self) -> bool {
+        match self.tokens.front() {
+            Some(token) => match token.token_type {
+                TokenType::INTEGER | TokenType::DOUBLE => true,
+                TokenType::ID => token.lexemme == BOOLEAN || token.lexemme == VOID,
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    // `f(` es una llamada a función
+    fn is_call(&self) -> bool {
+        matches!(
+            (self.tokens.front(), self.tokens.get(1)),
+            (Some(first), Some(second))
+                if first.token_type == TokenType::ID
+                    && second.token_type == TokenType::LPAR
+        )
+    }
+
+    // `return;` y `return x;` regresan de una función; `return = 1` sigue siendo una asignación
+    fn is_return(&self) -> bool {
+        matches!(
+            (self.tokens.front(), self.tokens.get(1)),
+            (Some(first), second)
+                if first.token_type == TokenType::ID
+                    && first.lexemme == RETURN
+                    && !matches!(
+                        second.map(|t| &t.token_type),
+                        Some(TokenType::ASSIGN | TokenType::INC | TokenType::DEC)
+                    )
+        )
+    }
+
     fn add_error(&mut self, error: ParseError) {
         self.errors.push(error);
     }
@@ -80,15 +192,46 @@ impl Parser {
                 return true;
             }
         }
-        return false;
+        false
     }
 
-    pub fn parse(mut self) -> (Option<TreeNode>, Vec<ParseError>) {
+    pub fn parse(mut self) -> (Option<Program>, Vec<ParseError>) {
         let root = self.programa();
         (root, self.errors)
     }
 
-    fn programa(&mut self) -> Option<TreeNode> {
+    pub fn parse_expression(mut self) -> (Option<TreeNode>, Vec<ParseError>) {
+        let node = self.expresion();
+        if let Some(Token {
+            token_type: TokenType::SCOL,
+            ..
+        }) = self.get_current_token()
+        {
+            self._match(TokenType::SCOL, true);
+        }
+        if let Some(token) = self.get_current_token().cloned() {
+            self.errors.push(ParseError {
+                message: "Se esperaba el fin de la expresión".to_string(),
+                expected_token_type: None,
+                current_token: Some(token),
+            });
+        } else if node.is_none() && self.errors.is_empty() {
+            self.errors.push(ParseError {
+                message: "Se esperaba una expresion".to_string(),
+                expected_token_type: None,
+                current_token: None,
+            });
+        }
+        (node, self.errors)
+    }
+
+    fn programa(&mut self) -> Option<Program> {
+        let mut functions = vec![];
+        while self.is_type() {
+            if let Some(function) = self.funcion() {
+                functions.push(function);
+            }
+        }
         if !self._match(TokenType::MAIN, true) {
             return None;
         };
@@ -96,7 +239,7 @@ impl Parser {
             return None;
         }
 
-        let root = self.lista_declaracion();
+        let body = self.lista_declaracion();
 
         /*  if root.is_none() {
             errors.push(ParseError {
@@ -110,7 +253,7 @@ impl Parser {
         let is_rbra = self._match(TokenType::RBRA, true);
         if !is_rbra {
             self.add_error(ParseError {
-                message: format!("Falta cerrar la llave del main!",),
+                message: "Falta cerrar la llave del main!".to_string(),
                 expected_token_type: None,
                 current_token,
             });
@@ -118,71 +261,154 @@ impl Parser {
         let token = self.get_current_token().cloned();
         if let Some(tkn) = token {
             self.add_error(ParseError {
-                message: format!("No se puede escribir fuera del cuerpo del main!",),
+                message: "No se puede escribir fuera del cuerpo del main!".to_string(),
                 expected_token_type: None,
                 current_token: Some(tkn.clone()),
             });
         }
 
-        root
+        Some(Program { functions, body })
+    }
+
+    fn funcion(&mut self) -> Option<TreeNode> {
+        let token = self.get_current_token().unwrap().clone();
+        self._match(token.token_type.clone(), true); // es seguro
+        let return_type = tipo(&token);
+        let name = self.get_current_token().cloned();
+        if !self._match(TokenType::ID, true) {
+            self.avanzar_hasta(TokenType::RBRA); // punto seguro
+            return None;
+        }
+        let cursor = self.current_cursor.clone();
+        if !self._match(TokenType::LPAR, true) {
+            self.avanzar_hasta(TokenType::RBRA); // punto seguro
+            return None;
+        }
+        let Some(params) = self.parametros() else {
+            self.avanzar_hasta(TokenType::RBRA); // punto seguro
+            return None;
+        };
+        if !self._match(TokenType::RPAR, true) || !self._match(TokenType::LBRA, true) {
+            self.avanzar_hasta(TokenType::RBRA); // punto seguro
+            return None;
+        }
+        let body = self.lista_declaracion();
+        let name = name.unwrap().lexemme; // seguro
+        let current_token = self.get_current_token().cloned();
+        if !self._match(TokenType::RBRA, false) {
+            self.add_error(ParseError {
+                message: format!("Falta cerrar la llave de la función {}!", name),
+                expected_token_type: Some(vec![TokenType::RBRA]),
+                current_token,
+            });
+            return None;
+        }
+        Some(TreeNode::new(Node::Decl {
+            kind: DeclKind::Function {
+                name,
+                params,
+                return_type,
+                body,
+            },
+            id: self.new_id(),
+            cursor,
+        }))
     }
 
-    fn lista_declaracion(&mut self) -> Option<TreeNode> {
-        let mut t = self.declaracion();
+    fn parametros(&mut self) -> Option<Vec<TreeNode>> {
+        let mut params = vec![];
+        if let Some(Token {
+            token_type: TokenType::RPAR,
+            ..
+        }) = self.get_current_token()
+        {
+            return Some(params);
+        }
+        loop {
+            let token = self.get_current_token().cloned();
+            let is_param_type = self.is_type() && token.as_ref().is_some_and(|t| t.lexemme != VOID);
+            if !is_param_type {
+                self.errors.push(ParseError {
+                    message: "Se esperaba el tipo del parámetro".to_string(),
+                    expected_token_type: Some(vec![TokenType::INTEGER, TokenType::DOUBLE]),
+                    current_token: token,
+                });
+                return None;
+            }
+            let token = token.unwrap(); // seguro
+            self._match(token.token_type.clone(), true); // es seguro
+            let name = self.get_current_token().cloned();
+            if !self._match(TokenType::ID, true) {
+                return None;
+            }
+            let cursor = self.current_cursor.clone();
+            params.push(TreeNode::new(Node::Decl {
+                kind: DeclKind::Var {
+                    typ: tipo(&token),
+                    name: name.unwrap().lexemme, // seguro
+                },
+                id: self.new_id(),
+                cursor,
+            }));
+            match self.get_current_token() {
+                Some(Token {
+                    token_type: TokenType::COMMA,
+                    ..
+                }) => self._match(TokenType::COMMA, true),
+                _ => break,
+            };
+        }
+        Some(params)
+    }
 
-        if let Some(ta) = &mut t {
-            let mut last_sib = ta.get_last_sibling_mut();
-            while let Some(q_node) = self.declaracion() {
-                last_sib.sibling = Some(Box::new(q_node));
-                last_sib = last_sib.get_last_sibling_mut();
+    fn lista_declaracion(&mut self) -> Vec<TreeNode> {
+        let mut nodes = vec![];
+        loop {
+            let original_len = self.errors.len();
+            let declaraciones = self.declaracion();
+            // una declaración con errores no termina la lista
+            if declaraciones.is_empty() && self.errors.len() == original_len {
+                break;
             }
+            nodes.extend(declaraciones);
         }
-        t
+        nodes
     }
 
-    fn declaracion(&mut self) -> Option<TreeNode> {
+    fn declaracion(&mut self) -> Vec<TreeNode> {
+        let is_boolean = self.is_boolean_declaration();
         match self.get_current_token() {
             Some(token) => match token.token_type {
                 TokenType::INTEGER | TokenType::DOUBLE => self.declaracion_variable(),
+                TokenType::ID if is_boolean => self.declaracion_variable(),
                 _ => self.lista_sentencias(),
             },
-            None => None,
+            None => vec![],
         }
     }
 
-    fn declaracion_variable(&mut self) -> Option<TreeNode> {
-        let typ = self.get_current_token().unwrap().token_type.clone();
-        let typ2 = typ.clone();
+    fn declaracion_variable(&mut self) -> Vec<TreeNode> {
+        let token = self.get_current_token().unwrap().clone();
         self._match(
-            typ, // es seguro
+            token.token_type.clone(), // es seguro
             true,
         );
-        let exp_type = match typ2 {
-            TokenType::INTEGER => ExpType::Integer,
-            TokenType::DOUBLE => ExpType::Float,
-            _ => ExpType::Void, // no deberia suceder
-        };
-        let node = self.identificador(exp_type);
+        let node = self.identificador(tipo(&token));
         self._match(TokenType::SCOL, true);
         node
     }
 
-    fn identificador(&mut self, typ: ExpType) -> Option<TreeNode> {
+    fn identificador(&mut self, typ: ExpType) -> Vec<TreeNode> {
         match self.get_current_token().cloned() {
             Some(token) => {
                 if !self._match(TokenType::ID, true) {
-                    return None;
+                    return vec![];
                 }
                 let cursor = self.current_cursor.clone();
-                let mut node = TreeNode::new(Node::Decl {
-                    kind: DeclKind::Var {
-                        typ: typ.clone(),
-                        name: token.lexemme.clone(),
-                    },
-                    id: Uuid::new_v4().to_string(),
-                    cursor,
-                });
-                let mut current_node = &mut node;
+                let Some(node) = self.variable(typ.clone(), token.lexemme.clone(), cursor) else {
+                    return vec![];
+                };
+                let mut nodes = vec![node];
 
                 while let Some(Token {
                     token_type: TokenType::COMMA,
@@ -195,50 +421,86 @@ impl Parser {
                         break;
                     }
                     let cursor = self.current_cursor.clone();
-
-                    let sibling_node = TreeNode::new(Node::Decl {
-                        cursor,
-                        kind: DeclKind::Var {
-                            typ: typ.clone(),
-                            name: token_op.unwrap().lexemme.clone(), // seguro
-                        },
-                        id: Uuid::new_v4().to_string(),
-                    });
-                    current_node.sibling = Some(Box::new(sibling_node));
-                    current_node = current_node.sibling.as_mut().unwrap();
+                    let name = token_op.unwrap().lexemme.clone(); // seguro
+                    let Some(sibling_node) = self.variable(typ.clone(), name, cursor) else {
+                        break;
+                    };
+                    nodes.push(sibling_node);
                 }
 
-                Some(node)
+                nodes
             }
-            None => None, // no estoy seguro
+            None => vec![], // no estoy seguro
+        }
+    }
+
+    // Declaración de una variable o, si sigue `[tamaño]`, de un arreglo
+    fn variable(&mut self, typ: ExpType, name: String, cursor: Option<Cursor>) -> Option<TreeNode> {
+        if !self.is_left_bracket() {
+            return Some(TreeNode::new(Node::Decl {
+                kind: DeclKind::Var { typ, name },
+                id: self.new_id(),
+                cursor,
+            }));
+        }
+        self._match_bracket(TokenType::LBRACKET); // siempre es true
+        let token = self.get_current_token().cloned();
+        let size = token
+            .as_ref()
+            .filter(|token| token.token_type == TokenType::INT)
+            .and_then(|token| token.lexemme.parse::<usize>().ok())
+            .filter(|size| *size > 0);
+        let Some(size) = size else {
+            self.errors.push(ParseError {
+                message: "El tamaño del arreglo debe ser un entero positivo".to_string(),
+                expected_token_type: Some(vec![TokenType::INT]),
+                current_token: token,
+            });
+            self.hasta_fin_de_sentencia();
+            return None;
+        };
+        self._match(TokenType::INT, true); // siempre es true
+        if !self._match_bracket(TokenType::RBRACKET) {
+            self.hasta_fin_de_sentencia();
+            return None;
+        }
+        Some(TreeNode::new(Node::Decl {
+            kind: DeclKind::Array { typ, name, size },
+            id: self.new_id(),
+            cursor,
+        }))
+    }
+
+    // Descarta los tokens hasta el `;` sin consumirlo, para que quien lo espera no reporte otro error
+    fn hasta_fin_de_sentencia(&mut self) {
+        while self
+            .get_current_token()
+            .is_some_and(|token| token.token_type != TokenType::SCOL)
+        {
+            self.get_next_token();
+        }
+    }
+
+    // Posición `[expresión]` de un arreglo
+    fn indice(&mut self) -> Option<TreeNode> {
+        self._match_bracket(TokenType::LBRACKET);
+        let index = self.expresion()?;
+        if !self._match_bracket(TokenType::RBRACKET) {
+            return None;
         }
+        Some(index)
     }
 
-    fn lista_sentencias(&mut self) -> Option<TreeNode> {
-        let mut node = None;
-        let mut current_node: *mut TreeNode = std::ptr::null_mut();
+    fn lista_sentencias(&mut self) -> Vec<TreeNode> {
+        let mut nodes = vec![];
 
         while !self.tokens.is_empty() {
             let original_len = self.errors.len(); //Hay que checar la longitud de esta lista para ver si mas adelante va a haber errores
             let result = self.sentencia();
 
-            match result {
-                Some(new_node) => {
-                    if node.is_none() {
-                        node = Some(new_node);
-                        current_node = node.as_mut().unwrap().get_last_sibling_mut();
-                    } else {
-                        unsafe {
-                            (*current_node).sibling = Some(Box::new(new_node));
-                            current_node = (*current_node)
-                                .sibling
-                                .as_deref_mut()
-                                .unwrap()
-                                .get_last_sibling_mut();
-                        }
-                    }
-                }
-                None => {
+            match result.is_empty() {
+                false => nodes.extend(result),
+                true => {
                     /* if let Some(tkn) = get_current_token(tokens) {
                         if tkn.token_type == TokenType::RBRA {
                             break;
@@ -256,26 +518,29 @@ impl Parser {
             }
         }
 
-        node
+        nodes
     }
 
-    fn sentencia(&mut self) -> Option<TreeNode> {
+    fn sentencia(&mut self) -> Vec<TreeNode> {
         let token = self.get_current_token().cloned();
         if token.is_none() {
             // si es none, salir
-            return None;
+            return vec![];
         }
 
         let curr_token = token.as_ref().unwrap();
-        match curr_token.token_type {
+        let node = match curr_token.token_type {
             // es seguro
             TokenType::IF => self.seleccion(),
             TokenType::WHILE => self.iteracion(),
             TokenType::DO => self.repeticion(),
             TokenType::STDIN => self.sent_in(),
             TokenType::STDOUT => self.sent_out(),
+            TokenType::ID if self.is_boolean_declaration() => return self.declaracion_variable(),
+            TokenType::ID if self.is_return() => self.sent_return(),
+            TokenType::ID if self.is_call() => self.sent_llamada(),
             TokenType::ID => self.asignacion(),
-            TokenType::INTEGER | TokenType::DOUBLE => self.declaracion_variable(),
+            TokenType::INTEGER | TokenType::DOUBLE => return self.declaracion_variable(),
             _ => {
                 if curr_token.token_type != TokenType::RBRA {
                     // si no es fin del main
@@ -288,7 +553,8 @@ impl Parser {
                 }
                 None
             }
-        }
+        };
+        node.into_iter().collect()
     }
 
     fn asignacion(&mut self) -> Option<TreeNode> {
@@ -305,11 +571,23 @@ impl Parser {
             return None;
         }
         let cursor = self.current_cursor.clone();
+        let index = if self.is_left_bracket() {
+            match self.indice() {
+                Some(index) => Some(Box::new(index)),
+                None => {
+                    self.avanzar_hasta(TokenType::SCOL);
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
 
         match self.get_current_token().cloned() {
             Some(token) => {
                 match token.token_type {
-                    TokenType::INC | TokenType::DEC => {
+                    // `a[i]++` no está soportado, se reporta que falta el '='
+                    TokenType::INC | TokenType::DEC if index.is_none() => {
                         let operacion = match token.token_type {
                             TokenType::INC => TokenType::SUM,
                             TokenType::DEC => TokenType::MIN,
@@ -318,9 +596,10 @@ impl Parser {
                         self._match(token.token_type.clone(), true); // ++ o --
                         let node = Some(TreeNode::new(Node::Stmt {
                             cursor: cursor.clone(),
-                            id: Uuid::new_v4().to_string(),
+                            id: self.new_id(),
                             kind: StmtKind::Assign {
                                 exp_value: None,
+                                index: None,
                                 name: name.clone(),
                                 value: Box::new(TreeNode::new(Node::Exp {
                                     cursor: cursor.clone(),
@@ -331,19 +610,19 @@ impl Parser {
                                             cursor,
                                             typ: ExpType::Void,
                                             kind: ExpKind::Id { name: name.clone() },
-                                            id: Uuid::new_v4().to_string(),
+                                            id: self.new_id(),
                                             val: None,
                                         })),
                                         right: Some(Box::new(TreeNode::new(Node::Exp {
                                             cursor: None,
                                             typ: ExpType::Void,
                                             kind: ExpKind::Const { value: 1 },
-                                            id: Uuid::new_v4().to_string(),
+                                            id: self.new_id(),
                                             val: None,
                                         }))),
                                     },
                                     val: None,
-                                    id: Uuid::new_v4().to_string(),
+                                    id: self.new_id(),
                                 })),
                             },
                         }));
@@ -369,11 +648,12 @@ impl Parser {
         let value = self.sent_expresion()?;
         Some(TreeNode::new(Node::Stmt {
             cursor,
-            id: Uuid::new_v4().to_string(),
+            id: self.new_id(),
             kind: StmtKind::Assign {
                 exp_value: None,
                 name,
                 value: Box::new(value),
+                index,
             },
         }))
     }
@@ -490,18 +770,18 @@ impl Parser {
                 });
                 return None;
             }
-            r
+            Some(r)
         } else {
             None
         };
 
         Some(TreeNode::new(Node::Stmt {
             cursor,
-            id: Uuid::new_v4().to_string(),
+            id: self.new_id(),
             kind: StmtKind::If {
                 condition: Box::new(condition.unwrap()),
-                then_branch: then_branch.map(|n| Box::new(n)),
-                else_branch: else_branch.map(|n| Box::new(n)),
+                then_branch,
+                else_branch,
             },
         }))
     }
@@ -546,10 +826,10 @@ impl Parser {
                 }
                 Some(TreeNode::new(Node::Stmt {
                     cursor,
-                    id: Uuid::new_v4().to_string(),
+                    id: self.new_id(),
                     kind: StmtKind::While {
                         condition: Box::new(condition),
-                        body: body.map(|n| Box::new(n)),
+                        body,
                     },
                 }))
             }
@@ -609,9 +889,9 @@ impl Parser {
                 self._match(TokenType::SCOL, false);
                 Some(TreeNode::new(Node::Stmt {
                     cursor,
-                    id: Uuid::new_v4().to_string(),
+                    id: self.new_id(),
                     kind: StmtKind::Do {
-                        body: body.map(|n| Box::new(n)),
+                        body,
                         condition: Box::new(condition),
                     },
                 }))
@@ -625,20 +905,18 @@ impl Parser {
 
     fn sent_in(&mut self) -> Option<TreeNode> {
         self._match(TokenType::STDIN, true); // realmente no deberia pasar
-        let cursor = self.current_cursor.clone();
         let curr_token = self.get_current_token().cloned();
         if !self._match(TokenType::ID, true) {
             self.avanzar_hasta(TokenType::SCOL);
             return None;
         }
-        if curr_token.is_none() {
-            return None;
-        }
-        let name = curr_token.unwrap().lexemme.clone();
+        // el cursor es el de la variable, como en las asignaciones
+        let cursor = self.current_cursor.clone();
+        let name = curr_token?.lexemme;
         self._match(TokenType::SCOL, true);
         Some(TreeNode::new(Node::Stmt {
             cursor,
-            id: Uuid::new_v4().to_string(),
+            id: self.new_id(),
             kind: StmtKind::In { name },
         }))
     }
@@ -660,36 +938,109 @@ impl Parser {
         self._match(TokenType::SCOL, true);
         Some(TreeNode::new(Node::Stmt {
             cursor,
-            id: Uuid::new_v4().to_string(),
+            id: self.new_id(),
             kind: StmtKind::Out {
                 expression: Box::new(expression.unwrap()),
             },
         }))
     }
 
+    fn sent_return(&mut self) -> Option<TreeNode> {
+        self._match(TokenType::ID, true); // `return`, siempre es true
+        let cursor = self.current_cursor.clone();
+        let value = match self.get_current_token() {
+            Some(Token {
+                token_type: TokenType::SCOL,
+                ..
+            }) => None,
+            _ => match self.expresion() {
+                Some(value) => Some(Box::new(value)),
+                None => {
+                    self.avanzar_hasta(TokenType::SCOL);
+                    return None;
+                }
+            },
+        };
+        self._match(TokenType::SCOL, true);
+        Some(TreeNode::new(Node::Stmt {
+            cursor,
+            id: self.new_id(),
+            kind: StmtKind::Return { value },
+        }))
+    }
+
+    fn sent_llamada(&mut self) -> Option<TreeNode> {
+        let Some(call) = self.llamada() else {
+            self.avanzar_hasta(TokenType::SCOL);
+            return None;
+        };
+        self._match(TokenType::SCOL, true);
+        Some(TreeNode::new(Node::Stmt {
+            cursor: call.node.cursor().cloned(),
+            id: self.new_id(),
+            kind: StmtKind::Call {
+                expression: Box::new(call),
+            },
+        }))
+    }
+
+    fn llamada(&mut self) -> Option<TreeNode> {
+        let name = self.get_current_token().unwrap().lexemme.clone();
+        self._match(TokenType::ID, true); // siempre es true
+        let cursor = self.current_cursor.clone();
+        self._match(TokenType::LPAR, true); // siempre es true
+        let mut args = vec![];
+        if !matches!(
+            self.get_current_token(),
+            Some(Token {
+                token_type: TokenType::RPAR,
+                ..
+            })
+        ) {
+            loop {
+                args.push(self.expresion()?);
+                match self.get_current_token() {
+                    Some(Token {
+                        token_type: TokenType::COMMA,
+                        ..
+                    }) => self._match(TokenType::COMMA, true),
+                    _ => break,
+                };
+            }
+        }
+        if !self._match(TokenType::RPAR, true) {
+            // como en los paréntesis, quien use la expresión define el punto seguro
+            return None;
+        }
+        Some(TreeNode::new(Node::Exp {
+            cursor,
+            id: self.new_id(),
+            typ: ExpType::Void,
+            kind: ExpKind::Call { name, args },
+            val: None,
+        }))
+    }
+
     fn expresion(&mut self) -> Option<TreeNode> {
         let mut node = self.expresion_logica_and()?;
 
         if let Some(token) = self.get_current_token().cloned() {
-            match &token.token_type {
-                &TokenType::OR => {
-                    let op = token.token_type.clone();
-                    self._match(op.clone(), true); // siempre es true
-                    let cursor = self.current_cursor.clone();
-                    let right = self.expresion_logica_and()?;
-                    node = TreeNode::new(Node::Exp {
-                        cursor,
-                        id: Uuid::new_v4().to_string(),
-                        typ: ExpType::Void,
-                        kind: ExpKind::Op {
-                            op,
-                            left: Box::new(node),
-                            right: Some(Box::new(right)),
-                        },
-                        val: None,
-                    });
-                }
-                _ => {}
+            if token.token_type == TokenType::OR {
+                let op = token.token_type.clone();
+                self._match(op.clone(), true); // siempre es true
+                let cursor = self.current_cursor.clone();
+                let right = self.expresion_logica_and()?;
+                node = TreeNode::new(Node::Exp {
+                    cursor,
+                    id: self.new_id(),
+                    typ: ExpType::Void,
+                    kind: ExpKind::Op {
+                        op,
+                        left: Box::new(node),
+                        right: Some(Box::new(right)),
+                    },
+                    val: None,
+                });
             }
         }
 
@@ -700,25 +1051,22 @@ impl Parser {
         let mut node = self.expresion_logica_not()?;
 
         if let Some(token) = self.get_current_token().cloned() {
-            match &token.token_type {
-                &TokenType::AND => {
-                    let op = token.token_type.clone();
-                    self._match(op.clone(), true); // siempre es true
-                    let cursor = self.current_cursor.clone();
-                    let right = self.expresion_logica_not()?;
-                    node = TreeNode::new(Node::Exp {
-                        cursor,
-                        id: Uuid::new_v4().to_string(),
-                        typ: ExpType::Void,
-                        kind: ExpKind::Op {
-                            op,
-                            left: Box::new(node),
-                            right: Some(Box::new(right)),
-                        },
-                        val: None,
-                    });
-                }
-                _ => {}
+            if token.token_type == TokenType::AND {
+                let op = token.token_type.clone();
+                self._match(op.clone(), true); // siempre es true
+                let cursor = self.current_cursor.clone();
+                let right = self.expresion_logica_not()?;
+                node = TreeNode::new(Node::Exp {
+                    cursor,
+                    id: self.new_id(),
+                    typ: ExpType::Void,
+                    kind: ExpKind::Op {
+                        op,
+                        left: Box::new(node),
+                        right: Some(Box::new(right)),
+                    },
+                    val: None,
+                });
             }
         }
 
@@ -735,7 +1083,7 @@ impl Parser {
                     let left = self.expresion_rel()?;
                     Some(TreeNode::new(Node::Exp {
                         cursor,
-                        id: Uuid::new_v4().to_string(),
+                        id: self.new_id(),
                         typ: ExpType::Void,
                         kind: ExpKind::Op {
                             // unario
@@ -769,7 +1117,7 @@ impl Parser {
                     let right = self.expresion_simple()?;
                     node = TreeNode::new(Node::Exp {
                         cursor,
-                        id: Uuid::new_v4().to_string(),
+                        id: self.new_id(),
                         typ: ExpType::Void,
                         kind: ExpKind::Op {
                             op,
@@ -801,7 +1149,7 @@ impl Parser {
                     let right = self.termino()?;
                     node = TreeNode::new(Node::Exp {
                         cursor,
-                        id: Uuid::new_v4().to_string(),
+                        id: self.new_id(),
                         typ: ExpType::Void,
                         kind: ExpKind::Op {
                             op,
@@ -817,7 +1165,7 @@ impl Parser {
                         let right = self.termino()?;
                         node = TreeNode::new(Node::Exp {
                             cursor,
-                            id: Uuid::new_v4().to_string(),
+                            id: self.new_id(),
                             typ: ExpType::Void,
                             kind: ExpKind::Op {
                                 op: TokenType::SUM,
@@ -854,7 +1202,7 @@ impl Parser {
 
             node = TreeNode::new(Node::Exp {
                 cursor,
-                id: Uuid::new_v4().to_string(),
+                id: self.new_id(),
                 typ: ExpType::Void,
                 kind: ExpKind::Op {
                     op,
@@ -879,7 +1227,7 @@ impl Parser {
             let cursor = self.current_cursor.clone();
             node = TreeNode::new(Node::Exp {
                 cursor,
-                id: Uuid::new_v4().to_string(),
+                id: self.new_id(),
                 typ: ExpType::Void,
                 kind: ExpKind::Op {
                     op,
@@ -911,7 +1259,7 @@ impl Parser {
                     let cursor = self.current_cursor.clone();
                     Some(TreeNode::new(Node::Exp {
                         cursor,
-                        id: Uuid::new_v4().to_string(),
+                        id: self.new_id(),
                         kind: ExpKind::Const { value },
                         typ: ExpType::Void,
                         val: None,
@@ -923,12 +1271,26 @@ impl Parser {
                     let cursor = self.current_cursor.clone();
                     Some(TreeNode::new(Node::Exp {
                         cursor,
-                        id: Uuid::new_v4().to_string(),
+                        id: self.new_id(),
                         kind: ExpKind::ConstF { value },
                         typ: ExpType::Void,
                         val: None,
                     }))
                 }
+                TokenType::ID if token.lexemme == TRUE || token.lexemme == FALSE => {
+                    self._match(TokenType::ID, true); // siempre es true
+                    let cursor = self.current_cursor.clone();
+                    Some(TreeNode::new(Node::Exp {
+                        cursor,
+                        id: self.new_id(),
+                        kind: ExpKind::ConstB {
+                            value: token.lexemme == TRUE,
+                        },
+                        typ: ExpType::Void,
+                        val: None,
+                    }))
+                }
+                TokenType::ID if self.is_call() => self.llamada(),
                 TokenType::ID => self.incremento(),
                 _ => {
                     let expected_token_type = vec![TokenType::LPAR, TokenType::INT, TokenType::ID];
@@ -951,6 +1313,19 @@ impl Parser {
         let name = self.get_current_token().unwrap().lexemme.clone();
         self._match(TokenType::ID, true); // siempre es true
         let cursor = self.current_cursor.clone();
+        if self.is_left_bracket() {
+            let index = self.indice()?;
+            return Some(TreeNode::new(Node::Exp {
+                cursor,
+                id: self.new_id(),
+                typ: ExpType::Void,
+                kind: ExpKind::Index {
+                    name,
+                    index: Box::new(index),
+                },
+                val: None,
+            }));
+        }
         if matches!(
             self.get_current_token().unwrap().token_type,
             TokenType::INC | TokenType::DEC
@@ -962,24 +1337,23 @@ impl Parser {
                 _ => TokenType::SUM,
             }; // siempre es true
             self._match(op_token, true);
-            let cursor2 = self.current_cursor.clone();
 
             Some(TreeNode::new(Node::Exp {
                 cursor: cursor.clone(),
-                id: Uuid::new_v4().to_string(),
+                id: self.new_id(),
                 typ: ExpType::Void,
                 kind: ExpKind::Op {
                     op,
                     left: Box::new(TreeNode::new(Node::Exp {
-                        cursor: cursor2,
-                        id: Uuid::new_v4().to_string(),
+                        cursor,
+                        id: self.new_id(),
                         typ: ExpType::Void,
                         kind: ExpKind::Id { name },
                         val: None,
                     })),
                     right: Some(Box::new(TreeNode::new(Node::Exp {
                         cursor: None,
-                        id: Uuid::new_v4().to_string(),
+                        id: self.new_id(),
                         kind: ExpKind::Const { value: 1 },
                         typ: ExpType::Void,
                         val: None,
@@ -990,7 +1364,7 @@ impl Parser {
         } else {
             Some(TreeNode::new(Node::Exp {
                 cursor,
-                id: Uuid::new_v4().to_string(),
+                id: self.new_id(),
                 typ: ExpType::Void,
                 kind: ExpKind::Id { name },
                 val: None,
@@ -998,3 +1372,12 @@ impl Parser {
         }
     }
 }
+
+fn tipo(token: &Token) -> ExpType {
+    match token.token_type {
+        TokenType::INTEGER => ExpType::Integer,
+        TokenType::DOUBLE => ExpType::Float,
+        TokenType::ID if token.lexemme == BOOLEAN => ExpType::Boolean,
+        _ => ExpType::Void, // `void`
+    }
+}
@@ -0,0 +1,129 @@
+use super::structures::{AstError, Node, NodeId, TreeNode};
+
+// Entrada del índice: el nodo y el id de su padre (None para las sentencias del main y las
+// funciones)
+#[derive(Debug, Clone, Copy)]
+pub struct IndexEntry<'a> {
+    pub node: &'a TreeNode,
+    pub parent: Option<NodeId>,
+}
+
+// Índice de los nodos de un árbol ya construido por `NodeId`, con búsqueda en O(1) y enlaces al
+// padre. No es una arena: los nodos siguen siendo dueños de sus hijos en `TreeNode` y el índice
+// solo guarda referencias al árbol. Como los ids pueden tener huecos (los nodos que el parser
+// descarta también consumen uno), las entradas son opcionales
+#[derive(Debug, Clone, Default)]
+pub struct NodeIndex<'a> {
+    entries: Vec<Option<IndexEntry<'a>>>,
+}
+
+impl<'a> NodeIndex<'a> {
+    pub fn new(block: &'a [TreeNode]) -> Result<Self, Vec<AstError>> {
+        NodeIndex::from_blocks(&[block])
+    }
+
+    // Indexa varios bloques de primer nivel, como las funciones y el cuerpo del main. Un árbol
+    // leído con `--from-ast` puede repetir ids; en ese caso regresa un error por cada repetido,
+    // igual que `validate_program`
+    pub fn from_blocks(blocks: &[&'a [TreeNode]]) -> Result<Self, Vec<AstError>> {
+        let mut index = NodeIndex { entries: vec![] };
+        let mut errors = vec![];
+        // pila explícita para no depender de la profundidad del árbol
+        let mut pending: Vec<(&TreeNode, Option<NodeId>)> = blocks
+            .iter()
+            .flat_map(|block| block.iter())
+            .map(|node| (node, None))
+            .collect();
+        while let Some((tree_node, parent)) = pending.pop() {
+            let id = tree_node.node.id();
+            let entry = IndexEntry {
+                node: tree_node,
+                parent,
+            };
+            if !index.insert(id, entry) {
+                errors.push(AstError::new(
+                    &tree_node.node,
+                    &format!("El id {} está repetido", id.0),
+                ));
+            }
+            for child in tree_node.children() {
+                pending.push((child, Some(id)));
+            }
+        }
+        if errors.is_empty() {
+            Ok(index)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Regresa false, sin cambiar la entrada, si el id ya estaba
+    fn insert(&mut self, id: NodeId, entry: IndexEntry<'a>) -> bool {
+        let index = id.0 as usize;
+        if index >= self.entries.len() {
+            self.entries.resize(index + 1, None);
+        }
+        if self.entries[index].is_some() {
+            return false;
+        }
+        self.entries[index] = Some(entry);
+        true
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&'a Node> {
+        self.entry(id).map(|entry| &entry.node.node)
+    }
+
+    pub fn tree_node(&self, id: NodeId) -> Option<&'a TreeNode> {
+        self.entry(id).map(|entry| entry.node)
+    }
+
+    pub fn entry(&self, id: NodeId) -> Option<&IndexEntry<'a>> {
+        self.entries
+            .get(id.0 as usize)
+            .and_then(|entry| entry.as_ref())
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).and_then(|entry| entry.parent)
+    }
+
+    // Ids de los hijos directos, en el orden de `TreeNode::children`
+    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+        self.tree_node(id)
+            .map(|node| {
+                node.children()
+                    .into_iter()
+                    .map(|child| child.node.id())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Ids de los ancestros, del padre hacia la raíz
+    pub fn ancestors(&self, id: NodeId) -> Vec<NodeId> {
+        let mut ancestors = vec![];
+        let mut current = self.parent(id);
+        while let Some(parent) = current {
+            ancestors.push(parent);
+            current = self.parent(parent);
+        }
+        ancestors
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(|entry| entry.is_none())
+    }
+
+    // Nodos ordenados por id
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &IndexEntry<'a>)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| entry.as_ref().map(|e| (NodeId(index as u32), e)))
+    }
+}
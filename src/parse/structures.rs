@@ -1,74 +1,113 @@
 use scanner::data::{Cursor, Token, TokenType};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 //Valor para nodo exp ya sea Int o Float
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub enum NodeValue {
     Int(i32),
     Float(f32),
     Boolean(bool),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+// Identificador de nodo, indice dentro del `NodeIndex` del árbol
+#[derive(
+    Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[serde(transparent)]
+pub struct NodeId(pub u32);
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct ParseError {
+    #[schemars(with = "Option<crate::schema::TokenSchema>")]
     pub current_token: Option<Token>,
+    #[schemars(with = "Option<Vec<String>>")]
     pub expected_token_type: Option<Vec<TokenType>>,
     pub message: String,
 }
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+// Raíz del árbol: las funciones declaradas antes del main y las sentencias y declaraciones del
+// cuerpo del main
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Default)]
+pub struct Program {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<TreeNode>,
+    pub body: Vec<TreeNode>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 
 pub struct TreeNode {
-    //    pub children: Vec<Box<TreeNode>>,
-    pub sibling: Option<Box<TreeNode>>,
     pub node: Node,
 }
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 
 pub enum Node {
     // Tipo de Nodo
     Stmt {
         kind: StmtKind,
-        id: String,
+        id: NodeId,
+        #[schemars(with = "Option<crate::schema::CursorSchema>")]
         cursor: Option<Cursor>,
     },
     Exp {
         kind: ExpKind,
         typ: ExpType,
-        id: String,
+        id: NodeId,
+        #[schemars(with = "Option<crate::schema::CursorSchema>")]
         cursor: Option<Cursor>,
         val: Option<NodeValue>,
     },
     Decl {
         kind: DeclKind,
-        id: String,
+        id: NodeId,
+        #[schemars(with = "Option<crate::schema::CursorSchema>")]
         cursor: Option<Cursor>,
     },
 }
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub enum DeclKind {
-    Var { typ: ExpType, name: String },
+    Var {
+        typ: ExpType,
+        name: String,
+    },
+    // arreglo de tamaño fijo; `typ` es el tipo de sus elementos
+    Array {
+        typ: ExpType,
+        name: String,
+        size: usize,
+    },
+    // los parámetros son declaraciones `Var`; las funciones sin valor de regreso usan `Void`
+    Function {
+        name: String,
+        params: Vec<TreeNode>,
+        return_type: ExpType,
+        body: Vec<TreeNode>,
+    },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub enum StmtKind {
     // Sentencias soportadas
     If {
         condition: Box<TreeNode>,
-        then_branch: Option<Box<TreeNode>>,
-        else_branch: Option<Box<TreeNode>>,
+        then_branch: Vec<TreeNode>,
+        else_branch: Option<Vec<TreeNode>>,
     },
     While {
         condition: Box<TreeNode>,
-        body: Option<Box<TreeNode>>,
+        body: Vec<TreeNode>,
     },
     Do {
-        body: Option<Box<TreeNode>>,
+        body: Vec<TreeNode>,
         condition: Box<TreeNode>,
     },
     Assign {
         name: String,
         exp_value: Option<NodeValue>,
         value: Box<TreeNode>,
+        // posición del arreglo que se asigna, None para las variables
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        index: Option<Box<TreeNode>>,
     },
     In {
         name: String,
@@ -76,12 +115,20 @@ pub enum StmtKind {
     Out {
         expression: Box<TreeNode>,
     },
+    Return {
+        value: Option<Box<TreeNode>>,
+    },
+    // llamada a función usada como sentencia; `expression` siempre es un `ExpKind::Call`
+    Call {
+        expression: Box<TreeNode>,
+    },
 }
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 
 pub enum ExpKind {
     // Uso de operadores, constantes e identificadores
     Op {
+        #[schemars(with = "String")]
         op: TokenType,
         left: Box<TreeNode>,
         right: Option<Box<TreeNode>>,
@@ -92,11 +139,23 @@ pub enum ExpKind {
     ConstF {
         value: f32,
     },
+    ConstB {
+        value: bool,
+    },
     Id {
         name: String,
     },
+    Call {
+        name: String,
+        args: Vec<TreeNode>,
+    },
+    // elemento `name[index]` de un arreglo
+    Index {
+        name: String,
+        index: Box<TreeNode>,
+    },
 }
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 
 pub enum ExpType {
     // Para el tipado
@@ -106,23 +165,55 @@ pub enum ExpType {
     Boolean,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct SymbolData {
     pub mem_location: i32,
+    #[schemars(with = "crate::schema::CursorSchema")]
     pub declaration: Cursor,
     pub typ: ExpType,
     pub value: Option<NodeValue>,
     pub usages: Vec<SymbolReference>,
+    // nivel de anidamiento del bloque donde se declaró; 0 es el cuerpo del main
+    #[serde(default)]
+    pub scope: usize,
+    // función donde se declaró; None para las variables del main
+    #[serde(default)]
+    pub function: Option<String>,
+    // número de elementos de los arreglos, que ocupan las localidades desde `mem_location` hasta
+    // `mem_location + size - 1`; None para las variables
+    #[serde(default)]
+    pub size: Option<usize>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct AnalyzeError {
     pub message: String,
+    #[schemars(with = "crate::schema::CursorSchema")]
     pub cursor: Cursor,
 }
 
+// Error de un árbol que no tiene la forma que genera el parser, por ejemplo uno leído con
+// `--from-ast`
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AstError {
+    pub message: String,
+    pub id: NodeId,
+    pub cursor: Option<Cursor>,
+}
+
+impl AstError {
+    pub(crate) fn new(node: &Node, message: &str) -> Self {
+        AstError {
+            message: message.to_string(),
+            id: node.id(),
+            cursor: node.cursor().cloned(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 
 pub struct SymbolReference {
+    #[schemars(with = "crate::schema::CursorSchema")]
     pub cursor: Cursor,
 }
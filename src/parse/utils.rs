@@ -1,291 +1,242 @@
 use std::{
     collections::HashMap,
+    fmt,
     ops::{Add, Div, Mul, Rem, Sub},
 };
 
-use crate::structures::NodeValue;
+use scanner::data::Cursor;
 
-use super::structures::{DeclKind, ExpKind, Node, StmtKind, SymbolData, TreeNode};
+use crate::{
+    structures::NodeValue,
+    symbols::{format_symbol_table, SymbolFormat},
+};
+
+use super::{
+    node_index::NodeIndex,
+    structures::{
+        AstError, DeclKind, ExpKind, Node, NodeId, Program, StmtKind, SymbolData, TreeNode,
+    },
+};
 
 impl TreeNode {
     pub fn new(node: Node) -> Self {
-        TreeNode {
-            node,
-            //children: Vec::new(),
-            sibling: None,
-        }
+        TreeNode { node }
     }
 
-    /*   pub fn add_child(&mut self, child: TreeNode) {
-        self.children.push(Box::new(child));
-    } */
-    // Función para recorrer el árbol de manera preorden
-    pub fn pre_order_traversal(&self, visit: &mut dyn FnMut(&Node)) {
-        // Primero visitamos el nodo actual
-        visit(&self.node);
-
-        // Dependiendo del tipo de nodo, recorremos sus hijos
+    // Hijos directos del nodo, en el orden en que aparecen en el código
+    pub fn children(&self) -> Vec<&TreeNode> {
+        let mut children = vec![];
         match &self.node {
-            // Si el nodo es una sentencia (Stmt), verificamos su tipo
             Node::Stmt { kind, .. } => match kind {
                 StmtKind::If {
                     condition,
                     then_branch,
                     else_branch,
                 } => {
-                    // Visitamos la condición (que es un TreeNode) de manera recursiva
-                    condition.pre_order_traversal(visit);
-                    // Si existe el bloque "then", lo recorremos
-                    if let Some(then) = then_branch {
-                        then.pre_order_traversal(visit);
-                    }
-                    // Si existe el bloque "else", lo recorremos
-                    if let Some(else_) = else_branch {
-                        else_.pre_order_traversal(visit);
+                    children.push(condition.as_ref());
+                    children.extend(then_branch.iter());
+                    if let Some(else_branch) = else_branch {
+                        children.extend(else_branch.iter());
                     }
                 }
                 StmtKind::While { condition, body } => {
-                    // Visitamos la condición (que es un TreeNode) de manera recursiva
-                    condition.pre_order_traversal(visit);
-                    // Si existe el cuerpo, lo recorremos
-                    if let Some(body) = body {
-                        body.pre_order_traversal(visit);
-                    }
+                    children.push(condition.as_ref());
+                    children.extend(body.iter());
                 }
                 StmtKind::Do { body, condition } => {
-                    // Visitamos el cuerpo si existe
-                    if let Some(body) = body {
-                        body.pre_order_traversal(visit);
-                    }
-                    // Luego visitamos la condición (que es un Node) directamente
-                    condition.pre_order_traversal(visit);
-                }
-                StmtKind::Assign { value, .. } => {
-                    // Visitamos el valor de la asignación (que es un TreeNode) de manera recursiva
-                    value.pre_order_traversal(visit);
+                    children.extend(body.iter());
+                    children.push(condition.as_ref());
                 }
-                StmtKind::In { .. } => {
-                    // No tiene hijos
+                StmtKind::Assign { value, index, .. } => {
+                    children.extend(index.as_deref());
+                    children.push(value.as_ref());
                 }
-                StmtKind::Out { expression } => {
-                    // Para el caso de "Out", visitamos la expresión
-                    expression.pre_order_traversal(visit);
+                StmtKind::In { .. } => {}
+                StmtKind::Out { expression } | StmtKind::Call { expression } => {
+                    children.push(expression.as_ref())
                 }
+                StmtKind::Return { value } => children.extend(value.as_deref()),
             },
-            // Si el nodo es una expresión (Exp), verificamos su tipo
             Node::Exp { kind, .. } => match kind {
                 ExpKind::Op { left, right, .. } => {
-                    // Visitamos el lado izquierdo de manera recursiva
-                    left.pre_order_traversal(visit);
-                    // Si hay un lado derecho, también lo visitamos de manera recursiva
-                    if let Some(right) = right {
-                        right.pre_order_traversal(visit);
-                    }
-                }
-                ExpKind::Const { .. } | ExpKind::ConstF { .. } | ExpKind::Id { .. } => {
-                    // Estos nodos no tienen hijos
+                    children.push(left.as_ref());
+                    children.extend(right.as_deref());
                 }
+                ExpKind::Call { args, .. } => children.extend(args.iter()),
+                ExpKind::Index { index, .. } => children.push(index.as_ref()),
+                _ => {}
             },
-            // Si el nodo es una declaración (Decl), verificamos el tipo
-            Node::Decl { kind, .. } => match kind {
-                DeclKind::Var { .. } => {
-                    // No hay hijos en este caso
+            Node::Decl { kind, .. } => {
+                if let DeclKind::Function { params, body, .. } = kind {
+                    children.extend(params.iter());
+                    children.extend(body.iter());
                 }
-            },
-        }
-
-        // Finalmente, recorremos los hermanos si existen
-        if let Some(sibling) = &self.sibling {
-            sibling.pre_order_traversal(visit);
+            }
         }
+        children
     }
 
-    pub fn post_order_traversal(&self, visit: &mut dyn FnMut(&Node)) {
-        // Primero recorremos los hijos dependiendo del tipo de nodo
-        match &self.node {
-            // Si el nodo es una sentencia (Stmt), verificamos su tipo
+    pub fn children_mut(&mut self) -> Vec<&mut TreeNode> {
+        let mut children = vec![];
+        match &mut self.node {
             Node::Stmt { kind, .. } => match kind {
                 StmtKind::If {
                     condition,
                     then_branch,
                     else_branch,
                 } => {
-                    // Visitamos la condición (que es un TreeNode) de manera recursiva
-                    condition.post_order_traversal(visit);
-                    // Si existe el bloque "then", lo recorremos
-                    if let Some(then) = then_branch {
-                        then.post_order_traversal(visit);
-                    }
-                    // Si existe el bloque "else", lo recorremos
-                    if let Some(else_) = else_branch {
-                        else_.post_order_traversal(visit);
+                    children.push(condition.as_mut());
+                    children.extend(then_branch.iter_mut());
+                    if let Some(else_branch) = else_branch {
+                        children.extend(else_branch.iter_mut());
                     }
                 }
                 StmtKind::While { condition, body } => {
-                    // Visitamos la condición (que es un TreeNode) de manera recursiva
-                    condition.post_order_traversal(visit);
-                    // Si existe el cuerpo, lo recorremos
-                    if let Some(body) = body {
-                        body.post_order_traversal(visit);
-                    }
+                    children.push(condition.as_mut());
+                    children.extend(body.iter_mut());
                 }
                 StmtKind::Do { body, condition } => {
-                    // Visitamos el cuerpo si existe
-                    if let Some(body) = body {
-                        body.post_order_traversal(visit);
-                    }
-                    // Luego visitamos la condición (que es un Node) directamente
-                    condition.post_order_traversal(visit);
-                }
-                StmtKind::Assign { value, .. } => {
-                    // Visitamos el valor de la asignación (que es un TreeNode) de manera recursiva
-                    value.post_order_traversal(visit);
+                    children.extend(body.iter_mut());
+                    children.push(condition.as_mut());
                 }
-                StmtKind::In { .. } => {
-                    // No tiene hijos
+                StmtKind::Assign { value, index, .. } => {
+                    children.extend(index.as_deref_mut());
+                    children.push(value.as_mut());
                 }
-                StmtKind::Out { expression } => {
-                    // Para el caso de "Out", visitamos la expresión
-                    expression.post_order_traversal(visit);
+                StmtKind::In { .. } => {}
+                StmtKind::Out { expression } | StmtKind::Call { expression } => {
+                    children.push(expression.as_mut())
                 }
+                StmtKind::Return { value } => children.extend(value.as_deref_mut()),
             },
-            // Si el nodo es una expresión (Exp), verificamos su tipo
             Node::Exp { kind, .. } => match kind {
                 ExpKind::Op { left, right, .. } => {
-                    // Visitamos el lado izquierdo de manera recursiva
-                    left.post_order_traversal(visit);
-                    // Si hay un lado derecho, también lo visitamos de manera recursiva
-                    if let Some(right) = right {
-                        right.post_order_traversal(visit);
-                    }
-                }
-                ExpKind::Const { .. } | ExpKind::ConstF { .. } | ExpKind::Id { .. } => {
-                    // Estos nodos no tienen hijos
+                    children.push(left.as_mut());
+                    children.extend(right.as_deref_mut());
                 }
+                ExpKind::Call { args, .. } => children.extend(args.iter_mut()),
+                ExpKind::Index { index, .. } => children.push(index.as_mut()),
+                _ => {}
             },
-            // Si el nodo es una declaración (Decl), verificamos el tipo
-            Node::Decl { kind, .. } => match kind {
-                DeclKind::Var { .. } => {
-                    // No hay hijos en este caso
+            Node::Decl { kind, .. } => {
+                if let DeclKind::Function { params, body, .. } = kind {
+                    children.extend(params.iter_mut());
+                    children.extend(body.iter_mut());
                 }
-            },
+            }
         }
+        children
+    }
 
-        // Finalmente, visitamos el nodo actual
-        visit(&self.node);
+    // Función para recorrer el árbol de manera preorden. Usa una pila explícita, así que la
+    // longitud de los bloques no afecta la pila del programa
+    pub fn pre_order_traversal(&self, visit: &mut dyn FnMut(&Node)) {
+        let mut pending = vec![self];
+        while let Some(current) = pending.pop() {
+            visit(&current.node);
+            // los hijos se apilan al revés para visitarlos en orden
+            pending.extend(current.children().into_iter().rev());
+        }
+    }
 
-        // Luego, recorremos los hermanos si existen
-        if let Some(sibling) = &self.sibling {
-            sibling.post_order_traversal(visit);
+    pub fn post_order_traversal(&self, visit: &mut dyn FnMut(&Node)) {
+        // cada nodo se apila dos veces: la primera para agregar sus hijos y la segunda para visitarlo
+        let mut pending = vec![(self, false)];
+        while let Some((current, children_done)) = pending.pop() {
+            if children_done {
+                visit(&current.node);
+            } else {
+                pending.push((current, true));
+                pending.extend(current.children().into_iter().rev().map(|c| (c, false)));
+            }
         }
     }
+
     pub fn post_order_traversal_mut(&mut self, visit: &mut dyn FnMut(&mut Node)) {
-        // Primero recorremos los hijos dependiendo del tipo de nodo
-        match &mut self.node {
-            // Si el nodo es una sentencia (Stmt), verificamos su tipo
-            Node::Stmt { kind, .. } => match kind {
-                StmtKind::If {
-                    condition,
-                    then_branch,
-                    else_branch,
-                } => {
-                    // Visitamos la condición (que es un TreeNode) de manera recursiva
-                    condition.post_order_traversal_mut(visit);
-                    // Si existe el bloque "then", lo recorremos
-                    if let Some(then) = then_branch {
-                        then.post_order_traversal_mut(visit);
-                    }
-                    // Si existe el bloque "else", lo recorremos
-                    if let Some(else_) = else_branch {
-                        else_.post_order_traversal_mut(visit);
-                    }
-                }
-                StmtKind::While { condition, body } => {
-                    // Visitamos la condición (que es un TreeNode) de manera recursiva
-                    condition.post_order_traversal_mut(visit);
-                    // Si existe el cuerpo, lo recorremos
-                    if let Some(body) = body {
-                        body.post_order_traversal_mut(visit);
-                    }
-                }
-                StmtKind::Do { body, condition } => {
-                    // Visitamos el cuerpo si existe
-                    if let Some(body) = body {
-                        body.post_order_traversal_mut(visit);
-                    }
-                    // Luego visitamos la condición (que es un Node) directamente
-                    condition.post_order_traversal_mut(visit);
-                }
-                StmtKind::Assign { value, .. } => {
-                    // Visitamos el valor de la asignación (que es un TreeNode) de manera recursiva
-                    value.post_order_traversal_mut(visit);
-                }
-                StmtKind::In { .. } => {
-                    // No tiene hijos
-                }
-                StmtKind::Out { expression } => {
-                    // Para el caso de "Out", visitamos la expresión
-                    expression.post_order_traversal_mut(visit);
-                }
-            },
-            // Si el nodo es una expresión (Exp), verificamos su tipo
-            Node::Exp { kind, .. } => match kind {
-                ExpKind::Op { left, right, .. } => {
-                    // Visitamos el lado izquierdo de manera recursiva
-                    left.post_order_traversal_mut(visit);
-                    // Si hay un lado derecho, también lo visitamos de manera recursiva
-                    if let Some(right) = right {
-                        right.post_order_traversal_mut(visit);
-                    }
-                }
-                ExpKind::Const { .. } | ExpKind::ConstF { .. } | ExpKind::Id { .. } => {
-                    // Estos nodos no tienen hijos
-                }
-            },
-            // Si el nodo es una declaración (Decl), verificamos el tipo
-            Node::Decl { kind, .. } => match kind {
-                DeclKind::Var { .. } => {
-                    // No hay hijos en este caso
-                }
-            },
+        post_order_mut(vec![self], visit);
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.tree_string());
+    }
+
+    // El mismo texto que imprime `print`
+    pub fn tree_string(&self) -> String {
+        let mut out = String::new();
+        write_tree(&mut out, self, 0).expect("escribir en un String no falla");
+        out
+    }
+
+    // Indexa todos los nodos del árbol por su id; falla si algún id está repetido
+    pub fn node_index(&self) -> Result<NodeIndex<'_>, Vec<AstError>> {
+        NodeIndex::new(std::slice::from_ref(self))
+    }
+}
+
+impl Program {
+    pub fn new(body: Vec<TreeNode>) -> Self {
+        Program {
+            functions: vec![],
+            body,
         }
+    }
 
-        // Finalmente, visitamos el nodo actual
-        visit(&mut self.node);
+    // Las funciones se recorren antes que el cuerpo del main, en el orden del código
+    pub fn pre_order_traversal(&self, visit: &mut dyn FnMut(&Node)) {
+        for node in self.functions.iter().chain(self.body.iter()) {
+            node.pre_order_traversal(visit);
+        }
+    }
 
-        // Luego, recorremos los hermanos si existen
-        if let Some(sibling) = &mut self.sibling {
-            sibling.post_order_traversal_mut(visit);
+    pub fn post_order_traversal(&self, visit: &mut dyn FnMut(&Node)) {
+        for node in self.functions.iter().chain(self.body.iter()) {
+            node.post_order_traversal(visit);
         }
     }
 
+    pub fn post_order_traversal_mut(&mut self, visit: &mut dyn FnMut(&mut Node)) {
+        post_order_mut(
+            self.functions
+                .iter_mut()
+                .chain(self.body.iter_mut())
+                .collect(),
+            visit,
+        );
+    }
+
     pub fn print(&self) {
-        print_tree(self, 0);
+        print!("{}", self.tree_string());
     }
-    pub fn last_sibling(&self) -> Option<&Box<TreeNode>> {
-        let mut current = self.sibling.as_ref();
-        while let Some(ref next) = current {
-            if next.sibling.is_none() {
-                break;
-            }
-            current = next.sibling.as_ref();
+
+    pub fn tree_string(&self) -> String {
+        let mut out = String::new();
+        write_block(&mut out, &self.functions, 0)
+            .and_then(|_| write_block(&mut out, &self.body, 0))
+            .expect("escribir en un String no falla");
+        out
+    }
+
+    pub fn node_index(&self) -> Result<NodeIndex<'_>, Vec<AstError>> {
+        NodeIndex::from_blocks(&[&self.functions, &self.body])
+    }
+}
+
+impl Node {
+    pub fn id(&self) -> NodeId {
+        match self {
+            Node::Stmt { id, .. } | Node::Exp { id, .. } | Node::Decl { id, .. } => *id,
         }
-        current
     }
-    pub fn get_last_sibling_mut(&mut self) -> &mut TreeNode {
-        // Primero, verificamos si hay un hermano
-        if let Some(ref mut sibling) = self.sibling {
-            // Si hay un hermano, llamamos recursivamente a la función en ese hermano
-            sibling.get_last_sibling_mut()
-        } else {
-            // Si no hay un hermano, retornamos una referencia mutable al nodo actual
-            self
+
+    pub fn cursor(&self) -> Option<&Cursor> {
+        match self {
+            Node::Stmt { cursor, .. } | Node::Exp { cursor, .. } | Node::Decl { cursor, .. } => {
+                cursor.as_ref()
+            }
         }
     }
 }
 
-impl Node {}
-
 impl Add for NodeValue {
     type Output = Option<Self>;
 
@@ -301,7 +252,7 @@ impl Add for NodeValue {
             }
 
             (NodeValue::Float(left), NodeValue::Float(right)) => {
-                Some(NodeValue::Float(left - right))
+                Some(NodeValue::Float(left + right))
             }
             (NodeValue::Int(_), NodeValue::Boolean(_))
             | (NodeValue::Float(_), NodeValue::Boolean(_))
@@ -476,12 +427,39 @@ impl NodeValue {
     }
 }
 
-fn print_tree(node: &TreeNode, indent: usize) {
+fn write_tree(out: &mut dyn fmt::Write, node: &TreeNode, indent: usize) -> fmt::Result {
     let indentation = " ".repeat(indent);
     match &node.node {
         Node::Decl { kind, .. } => match kind {
             DeclKind::Var { typ, name } => {
-                println!("{}Decl: Var (Type: {:?}, Name: {})", indentation, typ, name);
+                writeln!(
+                    out,
+                    "{}Decl: Var (Type: {:?}, Name: {})",
+                    indentation, typ, name
+                )?;
+            }
+            DeclKind::Array { typ, name, size } => {
+                writeln!(
+                    out,
+                    "{}Decl: Array (Type: {:?}, Name: {}, Size: {})",
+                    indentation, typ, name, size
+                )?;
+            }
+            DeclKind::Function {
+                name,
+                params,
+                return_type,
+                body,
+            } => {
+                writeln!(
+                    out,
+                    "{}Decl: Function (Return Type: {:?}, Name: {})",
+                    indentation, return_type, name
+                )?;
+                writeln!(out, "{}  Params:", indentation)?;
+                write_block(out, params, indent + 4)?;
+                writeln!(out, "{}  Body:", indentation)?;
+                write_block(out, body, indent + 4)?;
             }
         },
         Node::Stmt { kind, .. } => match kind {
@@ -490,110 +468,174 @@ fn print_tree(node: &TreeNode, indent: usize) {
                 then_branch,
                 else_branch,
             } => {
-                println!("{}Stmt: If", indentation);
-                println!("{}  Condition:", indentation);
-                print_tree(condition, indent + 4);
-                println!("{}  Then Branch:", indentation);
-                if let Some(then) = then_branch {
-                    print_tree(then, indent + 4);
-                }
+                writeln!(out, "{}Stmt: If", indentation)?;
+                writeln!(out, "{}  Condition:", indentation)?;
+                write_tree(out, condition, indent + 4)?;
+                writeln!(out, "{}  Then Branch:", indentation)?;
+                write_block(out, then_branch, indent + 4)?;
                 if let Some(else_branch) = else_branch {
-                    println!("{}  Else Branch:", indentation);
-                    print_tree(else_branch, indent + 4);
+                    writeln!(out, "{}  Else Branch:", indentation)?;
+                    write_block(out, else_branch, indent + 4)?;
                 }
             }
             StmtKind::While { condition, body } => {
-                println!("{}Stmt: While", indentation);
-                println!("{}  Condition:", indentation);
-                print_tree(condition, indent + 4);
-                println!("{}  Body:", indentation);
-                if let Some(body) = body {
-                    print_tree(body, indent + 4);
-                }
+                writeln!(out, "{}Stmt: While", indentation)?;
+                writeln!(out, "{}  Condition:", indentation)?;
+                write_tree(out, condition, indent + 4)?;
+                writeln!(out, "{}  Body:", indentation)?;
+                write_block(out, body, indent + 4)?;
             }
             StmtKind::Do { body, condition } => {
-                println!("{}Stmt: Do", indentation);
-                println!("{}  Body:", indentation);
-                if let Some(body) = body {
-                    print_tree(body, indent + 4);
-                }
-                println!("{}  Condition:", indentation);
-                print_tree(condition, indent + 4);
+                writeln!(out, "{}Stmt: Do", indentation)?;
+                writeln!(out, "{}  Body:", indentation)?;
+                write_block(out, body, indent + 4)?;
+                writeln!(out, "{}  Condition:", indentation)?;
+                write_tree(out, condition, indent + 4)?;
             }
             StmtKind::Assign {
                 name,
                 value,
                 exp_value,
-                ..
+                index,
             } => {
-                println!(
+                writeln!(
+                    out,
                     "{}Stmt: Assign (Name: {}) | Value: {:?}",
                     indentation, name, exp_value
-                );
-                println!("{}  Value:", indentation);
-                print_tree(value, indent + 4);
+                )?;
+                if let Some(index) = index {
+                    writeln!(out, "{}  Index:", indentation)?;
+                    write_tree(out, index, indent + 4)?;
+                }
+                writeln!(out, "{}  Value:", indentation)?;
+                write_tree(out, value, indent + 4)?;
             }
             StmtKind::In { name } => {
-                println!("{}Stmt: In (Name: {})", indentation, name);
+                writeln!(out, "{}Stmt: In (Name: {})", indentation, name)?;
             }
             StmtKind::Out { expression } => {
-                println!("{}Stmt: Out", indentation);
-                println!("{}  Expression:", indentation);
-                print_tree(expression, indent + 4);
+                writeln!(out, "{}Stmt: Out", indentation)?;
+                writeln!(out, "{}  Expression:", indentation)?;
+                write_tree(out, expression, indent + 4)?;
+            }
+            StmtKind::Return { value } => {
+                writeln!(out, "{}Stmt: Return", indentation)?;
+                if let Some(value) = value {
+                    writeln!(out, "{}  Value:", indentation)?;
+                    write_tree(out, value, indent + 4)?;
+                }
+            }
+            StmtKind::Call { expression } => {
+                writeln!(out, "{}Stmt: Call", indentation)?;
+                write_tree(out, expression, indent + 2)?;
             }
         },
         Node::Exp { kind, val, typ, .. } => match kind {
             ExpKind::Op { op, left, right } => {
-                print!("{}Exp: Op ({:?}) | Type {:?}", indentation, op, typ);
+                write!(out, "{}Exp: Op ({:?}) | Type {:?}", indentation, op, typ)?;
                 if let Some(value) = val {
                     match value {
-                        NodeValue::Int(i) => println!(" Val: {}", i),
-                        NodeValue::Float(f) => println!(" Val: {}", f),
-                        NodeValue::Boolean(b) => println!(" Val: {}", b),
+                        NodeValue::Int(i) => writeln!(out, " Val: {}", i)?,
+                        NodeValue::Float(f) => writeln!(out, " Val: {}", f)?,
+                        NodeValue::Boolean(b) => writeln!(out, " Val: {}", b)?,
                     }
                 } else {
-                    println!();
+                    writeln!(out)?;
                 }
-                println!("{}  Left:", indentation);
-                print_tree(left, indent + 4);
-                println!("{}  Right:", indentation);
+                writeln!(out, "{}  Left:", indentation)?;
+                write_tree(out, left, indent + 4)?;
+                writeln!(out, "{}  Right:", indentation)?;
                 if let Some(right_node) = right {
-                    print_tree(&right_node, indent + 4);
+                    write_tree(out, right_node, indent + 4)?;
                 }
             }
             ExpKind::Const { value } => {
-                println!(
+                writeln!(
+                    out,
                     "{}Exp: Const (Value: {} | Type: {:?})",
                     indentation, value, typ
-                );
+                )?;
             }
             ExpKind::ConstF { value } => {
-                println!(
+                writeln!(
+                    out,
                     "{}Exp: Const Float (Value: {}) | Type: {:?})",
                     indentation, value, typ
-                );
+                )?;
+            }
+            ExpKind::ConstB { value } => {
+                writeln!(
+                    out,
+                    "{}Exp: Const Boolean (Value: {} | Type: {:?})",
+                    indentation, value, typ
+                )?;
             }
             ExpKind::Id { name } => {
-                println!("{}Exp: Id (Name: {}) | Type: {:?}", indentation, name, typ);
+                writeln!(
+                    out,
+                    "{}Exp: Id (Name: {}) | Type: {:?}",
+                    indentation, name, typ
+                )?;
+            }
+            ExpKind::Call { name, args } => {
+                writeln!(
+                    out,
+                    "{}Exp: Call (Name: {}) | Type: {:?}",
+                    indentation, name, typ
+                )?;
+                writeln!(out, "{}  Args:", indentation)?;
+                write_block(out, args, indent + 4)?;
+            }
+            ExpKind::Index { name, index } => {
+                writeln!(
+                    out,
+                    "{}Exp: Index (Name: {}) | Type: {:?}",
+                    indentation, name, typ
+                )?;
+                writeln!(out, "{}  Index:", indentation)?;
+                write_tree(out, index, indent + 4)?;
             }
         },
     }
-    if let Some(sibling) = &node.sibling {
-        print_tree(sibling, indent);
-    }
+    Ok(())
 }
 
-pub fn print_sym_table(table: &HashMap<String, SymbolData>) {
-    println!("------ TABLA DE SIMBOLOS --------");
-    for (k, v) in table.iter() {
-        print!(
-            "Variable:  {}  | Position ({},{}) | Type: {:?} | Value: {:?} | Location {} | Usages: ",
-            k, v.declaration.lin, v.declaration.col, v.typ, v.value, v.mem_location
-        );
-        for usage in v.usages.iter() {
-            print!("({}, {}),", usage.cursor.lin, usage.cursor.col,)
+// Recorrido en postorden de varios árboles con la misma pila que `post_order_traversal`. Un
+// `&mut` al padre no puede convivir con los de sus hijos, así que la pila guarda apuntadores y cada
+// nodo se toma prestado solo mientras se usa
+fn post_order_mut(roots: Vec<&mut TreeNode>, visit: &mut dyn FnMut(&mut Node)) {
+    let mut pending: Vec<(*mut TreeNode, bool)> = roots
+        .into_iter()
+        .rev()
+        .map(|root| (root as *mut TreeNode, false))
+        .collect();
+    while let Some((current, children_done)) = pending.pop() {
+        // SAFETY: los apuntadores salen de préstamos exclusivos del árbol que siguen vivos durante
+        // el recorrido y apuntan a subárboles disjuntos. Un nodo se visita después de todos sus
+        // descendientes, cuando ya no queda ningún apuntador a ellos en la pila
+        let current = unsafe { &mut *current };
+        if children_done {
+            visit(&mut current.node);
+        } else {
+            pending.push((current as *mut TreeNode, true));
+            pending.extend(
+                current
+                    .children_mut()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child as *mut TreeNode, false)),
+            );
         }
-        println!();
     }
-    println!("------ TABLA DE SIMBOLOS --------");
+}
+
+fn write_block(out: &mut dyn fmt::Write, block: &[TreeNode], indent: usize) -> fmt::Result {
+    for node in block.iter() {
+        write_tree(out, node, indent)?;
+    }
+    Ok(())
+}
+
+pub fn print_sym_table(table: &HashMap<String, SymbolData>) {
+    print!("{}", format_symbol_table(table, SymbolFormat::Text));
 }
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use scanner::data::TokenType;
+
+use super::{
+    structures::{AstError, DeclKind, ExpKind, ExpType, Node, Program, TreeNode},
+    visit::{walk_program, Context, Edge, Visitor, Walk},
+};
+
+// Verifica que un árbol cargado desde JSON tenga la forma que genera el parser antes de
+// pasarlo al `Analyzer`: funciones solo antes del main, sentencias en los bloques, expresiones
+// donde se esperan, operadores con el número correcto de operandos, arreglos con al menos un
+// elemento, ids sin repetir y cursores donde el análisis los usa
+pub fn validate_program(program: &Program) -> Result<(), Vec<AstError>> {
+    let mut validator = Validator {
+        errors: vec![],
+        ids: HashSet::new(),
+    };
+    walk_program(&mut validator, program);
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+struct Validator {
+    errors: Vec<AstError>,
+    ids: HashSet<u32>,
+}
+
+impl Validator {
+    fn error(&mut self, node: &TreeNode, message: &str) {
+        self.errors.push(AstError::new(&node.node, message));
+    }
+}
+
+fn is_binary(op: &TokenType) -> bool {
+    matches!(
+        op,
+        TokenType::OR
+            | TokenType::AND
+            | TokenType::LT
+            | TokenType::LE
+            | TokenType::GT
+            | TokenType::GE
+            | TokenType::EQ
+            | TokenType::NE
+            | TokenType::SUM
+            | TokenType::MIN
+            | TokenType::TIMES
+            | TokenType::DIV
+            | TokenType::MODULUS
+            | TokenType::POWER
+    )
+}
+
+impl Visitor for Validator {
+    fn enter_node(&mut self, node: &TreeNode, ctx: &Context) -> Walk {
+        if !self.ids.insert(ctx.id.0) {
+            self.error(node, &format!("El id {} está repetido", ctx.id.0));
+        }
+        // solo las constantes de `x++` se generan sin cursor
+        let is_const = matches!(
+            &node.node,
+            Node::Exp {
+                kind: ExpKind::Const { .. } | ExpKind::ConstF { .. } | ExpKind::ConstB { .. },
+                ..
+            }
+        );
+        if ctx.cursor.is_none() && !is_const {
+            self.error(node, "El nodo no tiene cursor");
+        }
+        let in_block = matches!(
+            ctx.edge,
+            Edge::Main | Edge::ThenBranch | Edge::ElseBranch | Edge::Body
+        );
+        let is_function = matches!(
+            &node.node,
+            Node::Decl {
+                kind: DeclKind::Function { .. },
+                ..
+            }
+        );
+        let message = match (&node.node, ctx.edge) {
+            (_, Edge::Function) if is_function => None,
+            (_, Edge::Function) => Some("Se esperaba una función"),
+            (
+                Node::Decl {
+                    kind: DeclKind::Var { .. },
+                    ..
+                },
+                Edge::Params,
+            ) => None,
+            (_, Edge::Params) => Some("Se esperaba un parámetro"),
+            _ if is_function => Some("Las funciones solo se pueden declarar antes del main"),
+            (Node::Exp { .. }, _) if in_block => {
+                Some("Se esperaba una sentencia o declaración, pero se encontró una expresión")
+            }
+            (Node::Stmt { .. } | Node::Decl { .. }, _) if !in_block => {
+                Some("Se esperaba una expresión")
+            }
+            _ => None,
+        };
+        match message {
+            Some(message) => {
+                self.error(node, message);
+                Walk::SkipChildren
+            }
+            None => Walk::Continue,
+        }
+    }
+
+    fn visit_call_stmt(&mut self, expression: &TreeNode, ctx: &Context) -> Walk {
+        if !matches!(
+            &expression.node,
+            Node::Exp {
+                kind: ExpKind::Call { .. },
+                ..
+            }
+        ) {
+            self.errors.push(AstError {
+                message: "La sentencia no contiene una llamada a función".to_string(),
+                id: ctx.id,
+                cursor: ctx.cursor.clone(),
+            });
+            return Walk::SkipChildren;
+        }
+        Walk::Continue
+    }
+
+    fn visit_decl(&mut self, typ: &ExpType, name: &str, ctx: &Context) -> Walk {
+        if !matches!(typ, ExpType::Integer | ExpType::Float | ExpType::Boolean) {
+            self.errors.push(AstError {
+                message: format!("La variable {} tiene un tipo inválido: {:?}", name, typ),
+                id: ctx.id,
+                cursor: ctx.cursor.clone(),
+            });
+        }
+        Walk::Continue
+    }
+
+    fn visit_array(&mut self, typ: &ExpType, name: &str, size: usize, ctx: &Context) -> Walk {
+        let message = if !matches!(typ, ExpType::Integer | ExpType::Float | ExpType::Boolean) {
+            Some(format!(
+                "El arreglo {} tiene un tipo inválido: {:?}",
+                name, typ
+            ))
+        } else if size == 0 {
+            Some(format!(
+                "El arreglo {} debe tener al menos un elemento",
+                name
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            self.errors.push(AstError {
+                message,
+                id: ctx.id,
+                cursor: ctx.cursor.clone(),
+            });
+        }
+        Walk::Continue
+    }
+
+    fn visit_op(
+        &mut self,
+        op: &TokenType,
+        _left: &TreeNode,
+        right: Option<&TreeNode>,
+        _typ: &ExpType,
+        ctx: &Context,
+    ) -> Walk {
+        let message = match (right, op) {
+            (Some(_), op) if is_binary(op) => None,
+            (None, TokenType::NEG) => None,
+            (Some(_), TokenType::NEG) => Some("La negación no lleva lado derecho".to_string()),
+            (None, op) if is_binary(op) => Some(format!("Operacion {:?} sin lado derecho", op)),
+            (_, op) => Some(format!("{:?} no es un operador", op)),
+        };
+        if let Some(message) = message {
+            self.errors.push(AstError {
+                message,
+                id: ctx.id,
+                cursor: ctx.cursor.clone(),
+            });
+        }
+        Walk::Continue
+    }
+}
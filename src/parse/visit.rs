@@ -0,0 +1,739 @@
+use scanner::data::{Cursor, TokenType};
+
+use super::structures::{
+    DeclKind, ExpKind, ExpType, Node, NodeId, NodeValue, Program, StmtKind, TreeNode,
+};
+
+// Lugar que ocupa un nodo (o un bloque) dentro de su padre
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge {
+    Main,
+    Function,
+    Params,
+    Condition,
+    ThenBranch,
+    ElseBranch,
+    Body,
+    Value,
+    Expression,
+    Left,
+    Right,
+    Args,
+    Index,
+}
+
+impl Edge {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Edge::Main => "main",
+            Edge::Function => "function",
+            Edge::Params => "params",
+            Edge::Condition => "condition",
+            Edge::ThenBranch => "then_branch",
+            Edge::ElseBranch => "else_branch",
+            Edge::Body => "body",
+            Edge::Value => "value",
+            Edge::Expression => "expression",
+            Edge::Left => "left",
+            Edge::Right => "right",
+            Edge::Args => "args",
+            Edge::Index => "index",
+        }
+    }
+}
+
+// Indica si se deben recorrer los hijos del nodo actual
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Walk {
+    Continue,
+    SkipChildren,
+}
+
+// Información del nodo que se está visitando
+#[derive(Debug, Clone, PartialEq)]
+pub struct Context {
+    pub id: NodeId,
+    pub cursor: Option<Cursor>,
+    pub parent: Option<NodeId>,
+    pub depth: usize,
+    pub edge: Edge,
+}
+
+// Información del bloque que se está visitando. `owner` es None para el cuerpo del main y es la
+// función para su cuerpo
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockContext {
+    pub owner: Option<NodeId>,
+    pub depth: usize,
+    pub edge: Edge,
+}
+
+// Recorrido de solo lectura. `enter_node` se llama antes del hook de cada variante y
+// `exit_node` después de recorrer los hijos. Si alguno regresa `SkipChildren`, no se bajan los hijos
+#[allow(unused_variables)]
+pub trait Visitor {
+    fn enter_node(&mut self, node: &TreeNode, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn exit_node(&mut self, node: &TreeNode, ctx: &Context) {}
+    fn enter_block(&mut self, block: &[TreeNode], ctx: &BlockContext) -> Walk {
+        Walk::Continue
+    }
+    fn exit_block(&mut self, block: &[TreeNode], ctx: &BlockContext) {}
+
+    fn visit_decl(&mut self, typ: &ExpType, name: &str, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_array(&mut self, typ: &ExpType, name: &str, size: usize, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_function(
+        &mut self,
+        name: &str,
+        params: &[TreeNode],
+        return_type: &ExpType,
+        body: &[TreeNode],
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_if(
+        &mut self,
+        condition: &TreeNode,
+        then_branch: &[TreeNode],
+        else_branch: Option<&[TreeNode]>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_while(&mut self, condition: &TreeNode, body: &[TreeNode], ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_do(&mut self, body: &[TreeNode], condition: &TreeNode, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    // `index` es el índice de `a[i] = ...`, None si se asigna a una variable
+    fn visit_assign(
+        &mut self,
+        name: &str,
+        index: Option<&TreeNode>,
+        value: &TreeNode,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_in(&mut self, name: &str, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_out(&mut self, expression: &TreeNode, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_return(&mut self, value: Option<&TreeNode>, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_call_stmt(&mut self, expression: &TreeNode, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_op(
+        &mut self,
+        op: &TokenType,
+        left: &TreeNode,
+        right: Option<&TreeNode>,
+        typ: &ExpType,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_const(&mut self, value: i32, typ: &ExpType, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_const_f(&mut self, value: f32, typ: &ExpType, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_const_b(&mut self, value: bool, typ: &ExpType, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_id(&mut self, name: &str, typ: &ExpType, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_call(&mut self, name: &str, args: &[TreeNode], typ: &ExpType, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_index(&mut self, name: &str, index: &TreeNode, typ: &ExpType, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+}
+
+// Igual que `Visitor`, pero los hooks pueden modificar el árbol
+#[allow(unused_variables)]
+pub trait VisitorMut {
+    fn enter_node(&mut self, node: &mut TreeNode, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn exit_node(&mut self, node: &mut TreeNode, ctx: &Context) {}
+    fn enter_block(&mut self, block: &mut Vec<TreeNode>, ctx: &BlockContext) -> Walk {
+        Walk::Continue
+    }
+    fn exit_block(&mut self, block: &mut Vec<TreeNode>, ctx: &BlockContext) {}
+
+    fn visit_decl(&mut self, typ: &mut ExpType, name: &mut String, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_array(
+        &mut self,
+        typ: &mut ExpType,
+        name: &mut String,
+        size: &mut usize,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_function(
+        &mut self,
+        name: &mut String,
+        params: &mut Vec<TreeNode>,
+        return_type: &mut ExpType,
+        body: &mut Vec<TreeNode>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_if(
+        &mut self,
+        condition: &mut TreeNode,
+        then_branch: &mut Vec<TreeNode>,
+        else_branch: Option<&mut Vec<TreeNode>>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_while(
+        &mut self,
+        condition: &mut TreeNode,
+        body: &mut Vec<TreeNode>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_do(
+        &mut self,
+        body: &mut Vec<TreeNode>,
+        condition: &mut TreeNode,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_assign(
+        &mut self,
+        name: &mut String,
+        index: Option<&mut TreeNode>,
+        value: &mut TreeNode,
+        exp_value: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_in(&mut self, name: &mut String, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_out(&mut self, expression: &mut TreeNode, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_return(&mut self, value: Option<&mut TreeNode>, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_call_stmt(&mut self, expression: &mut TreeNode, ctx: &Context) -> Walk {
+        Walk::Continue
+    }
+    fn visit_op(
+        &mut self,
+        op: &mut TokenType,
+        left: &mut TreeNode,
+        right: Option<&mut TreeNode>,
+        typ: &mut ExpType,
+        val: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_const(
+        &mut self,
+        value: &mut i32,
+        typ: &mut ExpType,
+        val: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_const_f(
+        &mut self,
+        value: &mut f32,
+        typ: &mut ExpType,
+        val: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_const_b(
+        &mut self,
+        value: &mut bool,
+        typ: &mut ExpType,
+        val: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_id(
+        &mut self,
+        name: &mut String,
+        typ: &mut ExpType,
+        val: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_call(
+        &mut self,
+        name: &mut String,
+        args: &mut Vec<TreeNode>,
+        typ: &mut ExpType,
+        val: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+    fn visit_index(
+        &mut self,
+        name: &mut String,
+        index: &mut TreeNode,
+        typ: &mut ExpType,
+        val: &mut Option<NodeValue>,
+        ctx: &Context,
+    ) -> Walk {
+        Walk::Continue
+    }
+}
+
+fn context(node: &TreeNode, parent: Option<NodeId>, depth: usize, edge: Edge) -> Context {
+    Context {
+        id: node.node.id(),
+        cursor: node.node.cursor().cloned(),
+        parent,
+        depth,
+        edge,
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for function in program.functions.iter() {
+        walk_node(visitor, function, None, 0, Edge::Function);
+    }
+    walk_block(visitor, &program.body, None, 0, Edge::Main);
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    block: &[TreeNode],
+    owner: Option<NodeId>,
+    depth: usize,
+    edge: Edge,
+) {
+    let ctx = BlockContext { owner, depth, edge };
+    if visitor.enter_block(block, &ctx) == Walk::Continue {
+        for node in block.iter() {
+            walk_node(visitor, node, owner, depth, edge);
+        }
+    }
+    visitor.exit_block(block, &ctx);
+}
+
+pub fn walk_node<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    node: &TreeNode,
+    parent: Option<NodeId>,
+    depth: usize,
+    edge: Edge,
+) {
+    let ctx = context(node, parent, depth, edge);
+    let mut walk = visitor.enter_node(node, &ctx);
+    if walk == Walk::Continue {
+        walk = match &node.node {
+            Node::Decl { kind, .. } => match kind {
+                DeclKind::Var { typ, name } => visitor.visit_decl(typ, name, &ctx),
+                DeclKind::Array { typ, name, size } => visitor.visit_array(typ, name, *size, &ctx),
+                DeclKind::Function {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                } => visitor.visit_function(name, params, return_type, body, &ctx),
+            },
+            Node::Stmt { kind, .. } => match kind {
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } => visitor.visit_if(condition, then_branch, else_branch.as_deref(), &ctx),
+                StmtKind::While { condition, body } => visitor.visit_while(condition, body, &ctx),
+                StmtKind::Do { body, condition } => visitor.visit_do(body, condition, &ctx),
+                StmtKind::Assign {
+                    name, value, index, ..
+                } => visitor.visit_assign(name, index.as_deref(), value, &ctx),
+                StmtKind::In { name } => visitor.visit_in(name, &ctx),
+                StmtKind::Out { expression } => visitor.visit_out(expression, &ctx),
+                StmtKind::Return { value } => visitor.visit_return(value.as_deref(), &ctx),
+                StmtKind::Call { expression } => visitor.visit_call_stmt(expression, &ctx),
+            },
+            Node::Exp { kind, typ, .. } => match kind {
+                ExpKind::Op { op, left, right } => {
+                    visitor.visit_op(op, left, right.as_deref(), typ, &ctx)
+                }
+                ExpKind::Const { value } => visitor.visit_const(*value, typ, &ctx),
+                ExpKind::ConstF { value } => visitor.visit_const_f(*value, typ, &ctx),
+                ExpKind::ConstB { value } => visitor.visit_const_b(*value, typ, &ctx),
+                ExpKind::Id { name } => visitor.visit_id(name, typ, &ctx),
+                ExpKind::Call { name, args } => visitor.visit_call(name, args, typ, &ctx),
+                ExpKind::Index { name, index } => visitor.visit_index(name, index, typ, &ctx),
+            },
+        };
+    }
+    if walk == Walk::Continue {
+        let (id, depth) = (Some(ctx.id), depth + 1);
+        match &node.node {
+            Node::Stmt { kind, .. } => match kind {
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } => {
+                    walk_node(visitor, condition, id, depth, Edge::Condition);
+                    walk_block(visitor, then_branch, id, depth, Edge::ThenBranch);
+                    if let Some(else_branch) = else_branch {
+                        walk_block(visitor, else_branch, id, depth, Edge::ElseBranch);
+                    }
+                }
+                StmtKind::While { condition, body } => {
+                    walk_node(visitor, condition, id, depth, Edge::Condition);
+                    walk_block(visitor, body, id, depth, Edge::Body);
+                }
+                StmtKind::Do { body, condition } => {
+                    walk_block(visitor, body, id, depth, Edge::Body);
+                    walk_node(visitor, condition, id, depth, Edge::Condition);
+                }
+                StmtKind::Assign { value, index, .. } => {
+                    if let Some(index) = index {
+                        walk_node(visitor, index, id, depth, Edge::Index);
+                    }
+                    walk_node(visitor, value, id, depth, Edge::Value);
+                }
+                StmtKind::In { .. } => {}
+                StmtKind::Out { expression } | StmtKind::Call { expression } => {
+                    walk_node(visitor, expression, id, depth, Edge::Expression)
+                }
+                StmtKind::Return { value } => {
+                    if let Some(value) = value {
+                        walk_node(visitor, value, id, depth, Edge::Value);
+                    }
+                }
+            },
+            Node::Exp { kind, .. } => match kind {
+                ExpKind::Op { left, right, .. } => {
+                    walk_node(visitor, left, id, depth, Edge::Left);
+                    if let Some(right) = right {
+                        walk_node(visitor, right, id, depth, Edge::Right);
+                    }
+                }
+                ExpKind::Call { args, .. } => {
+                    for arg in args.iter() {
+                        walk_node(visitor, arg, id, depth, Edge::Args);
+                    }
+                }
+                ExpKind::Index { index, .. } => walk_node(visitor, index, id, depth, Edge::Index),
+                _ => {}
+            },
+            Node::Decl { kind, .. } => {
+                if let DeclKind::Function { params, body, .. } = kind {
+                    for param in params.iter() {
+                        walk_node(visitor, param, id, depth, Edge::Params);
+                    }
+                    walk_block(visitor, body, id, depth, Edge::Body);
+                }
+            }
+        }
+    }
+    visitor.exit_node(node, &ctx);
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for function in program.functions.iter_mut() {
+        walk_node_mut(visitor, function, None, 0, Edge::Function);
+    }
+    walk_block_mut(visitor, &mut program.body, None, 0, Edge::Main);
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    block: &mut Vec<TreeNode>,
+    owner: Option<NodeId>,
+    depth: usize,
+    edge: Edge,
+) {
+    let ctx = BlockContext { owner, depth, edge };
+    if visitor.enter_block(block, &ctx) == Walk::Continue {
+        for node in block.iter_mut() {
+            walk_node_mut(visitor, node, owner, depth, edge);
+        }
+    }
+    visitor.exit_block(block, &ctx);
+}
+
+pub fn walk_node_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut TreeNode,
+    parent: Option<NodeId>,
+    depth: usize,
+    edge: Edge,
+) {
+    let ctx = context(node, parent, depth, edge);
+    let mut walk = visitor.enter_node(node, &ctx);
+    if walk == Walk::Continue {
+        walk = match &mut node.node {
+            Node::Decl { kind, .. } => match kind {
+                DeclKind::Var { typ, name } => visitor.visit_decl(typ, name, &ctx),
+                DeclKind::Array { typ, name, size } => visitor.visit_array(typ, name, size, &ctx),
+                DeclKind::Function {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                } => visitor.visit_function(name, params, return_type, body, &ctx),
+            },
+            Node::Stmt { kind, .. } => match kind {
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } => visitor.visit_if(condition, then_branch, else_branch.as_mut(), &ctx),
+                StmtKind::While { condition, body } => visitor.visit_while(condition, body, &ctx),
+                StmtKind::Do { body, condition } => visitor.visit_do(body, condition, &ctx),
+                StmtKind::Assign {
+                    name,
+                    value,
+                    exp_value,
+                    index,
+                } => visitor.visit_assign(name, index.as_deref_mut(), value, exp_value, &ctx),
+                StmtKind::In { name } => visitor.visit_in(name, &ctx),
+                StmtKind::Out { expression } => visitor.visit_out(expression, &ctx),
+                StmtKind::Return { value } => visitor.visit_return(value.as_deref_mut(), &ctx),
+                StmtKind::Call { expression } => visitor.visit_call_stmt(expression, &ctx),
+            },
+            Node::Exp { kind, typ, val, .. } => match kind {
+                ExpKind::Op { op, left, right } => {
+                    visitor.visit_op(op, left, right.as_deref_mut(), typ, val, &ctx)
+                }
+                ExpKind::Const { value } => visitor.visit_const(value, typ, val, &ctx),
+                ExpKind::ConstF { value } => visitor.visit_const_f(value, typ, val, &ctx),
+                ExpKind::ConstB { value } => visitor.visit_const_b(value, typ, val, &ctx),
+                ExpKind::Id { name } => visitor.visit_id(name, typ, val, &ctx),
+                ExpKind::Call { name, args } => visitor.visit_call(name, args, typ, val, &ctx),
+                ExpKind::Index { name, index } => visitor.visit_index(name, index, typ, val, &ctx),
+            },
+        };
+    }
+    if walk == Walk::Continue {
+        let (id, depth) = (Some(ctx.id), depth + 1);
+        match &mut node.node {
+            Node::Stmt { kind, .. } => match kind {
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } => {
+                    walk_node_mut(visitor, condition, id, depth, Edge::Condition);
+                    walk_block_mut(visitor, then_branch, id, depth, Edge::ThenBranch);
+                    if let Some(else_branch) = else_branch {
+                        walk_block_mut(visitor, else_branch, id, depth, Edge::ElseBranch);
+                    }
+                }
+                StmtKind::While { condition, body } => {
+                    walk_node_mut(visitor, condition, id, depth, Edge::Condition);
+                    walk_block_mut(visitor, body, id, depth, Edge::Body);
+                }
+                StmtKind::Do { body, condition } => {
+                    walk_block_mut(visitor, body, id, depth, Edge::Body);
+                    walk_node_mut(visitor, condition, id, depth, Edge::Condition);
+                }
+                StmtKind::Assign { value, index, .. } => {
+                    if let Some(index) = index {
+                        walk_node_mut(visitor, index, id, depth, Edge::Index);
+                    }
+                    walk_node_mut(visitor, value, id, depth, Edge::Value);
+                }
+                StmtKind::In { .. } => {}
+                StmtKind::Out { expression } | StmtKind::Call { expression } => {
+                    walk_node_mut(visitor, expression, id, depth, Edge::Expression)
+                }
+                StmtKind::Return { value } => {
+                    if let Some(value) = value {
+                        walk_node_mut(visitor, value, id, depth, Edge::Value);
+                    }
+                }
+            },
+            Node::Exp { kind, .. } => match kind {
+                ExpKind::Op { left, right, .. } => {
+                    walk_node_mut(visitor, left, id, depth, Edge::Left);
+                    if let Some(right) = right {
+                        walk_node_mut(visitor, right, id, depth, Edge::Right);
+                    }
+                }
+                ExpKind::Call { args, .. } => {
+                    for arg in args.iter_mut() {
+                        walk_node_mut(visitor, arg, id, depth, Edge::Args);
+                    }
+                }
+                ExpKind::Index { index, .. } => {
+                    walk_node_mut(visitor, index, id, depth, Edge::Index)
+                }
+                _ => {}
+            },
+            Node::Decl { kind, .. } => {
+                if let DeclKind::Function { params, body, .. } = kind {
+                    for param in params.iter_mut() {
+                        walk_node_mut(visitor, param, id, depth, Edge::Params);
+                    }
+                    walk_block_mut(visitor, body, id, depth, Edge::Body);
+                }
+            }
+        }
+    }
+    visitor.exit_node(node, &ctx);
+}
+
+// Reconstruye el árbol para pasadas de transformación. `fold_stmt` regresa un vector para
+// poder eliminar una sentencia (vector vacío) o reemplazarla por varias
+pub trait Fold {
+    fn fold_program(&mut self, program: Program) -> Program {
+        Program {
+            functions: self.fold_block(program.functions),
+            body: self.fold_block(program.body),
+        }
+    }
+
+    fn fold_block(&mut self, block: Vec<TreeNode>) -> Vec<TreeNode> {
+        let mut folded = Vec::with_capacity(block.len());
+        for node in block {
+            folded.extend(self.fold_stmt(node));
+        }
+        folded
+    }
+
+    fn fold_stmt(&mut self, node: TreeNode) -> Vec<TreeNode> {
+        vec![fold_children(self, node)]
+    }
+
+    fn fold_exp(&mut self, node: TreeNode) -> TreeNode {
+        fold_children(self, node)
+    }
+}
+
+// Aplica el `Fold` a los hijos del nodo y lo reconstruye
+pub fn fold_children<F: Fold + ?Sized>(folder: &mut F, node: TreeNode) -> TreeNode {
+    let node = match node.node {
+        Node::Stmt { kind, id, cursor } => {
+            let kind = match kind {
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } => StmtKind::If {
+                    condition: Box::new(folder.fold_exp(*condition)),
+                    then_branch: folder.fold_block(then_branch),
+                    else_branch: else_branch.map(|block| folder.fold_block(block)),
+                },
+                StmtKind::While { condition, body } => StmtKind::While {
+                    condition: Box::new(folder.fold_exp(*condition)),
+                    body: folder.fold_block(body),
+                },
+                StmtKind::Do { body, condition } => StmtKind::Do {
+                    body: folder.fold_block(body),
+                    condition: Box::new(folder.fold_exp(*condition)),
+                },
+                StmtKind::Assign {
+                    name,
+                    exp_value,
+                    value,
+                    index,
+                } => StmtKind::Assign {
+                    name,
+                    exp_value,
+                    value: Box::new(folder.fold_exp(*value)),
+                    index: index.map(|index| Box::new(folder.fold_exp(*index))),
+                },
+                StmtKind::In { name } => StmtKind::In { name },
+                StmtKind::Out { expression } => StmtKind::Out {
+                    expression: Box::new(folder.fold_exp(*expression)),
+                },
+                StmtKind::Return { value } => StmtKind::Return {
+                    value: value.map(|value| Box::new(folder.fold_exp(*value))),
+                },
+                StmtKind::Call { expression } => StmtKind::Call {
+                    expression: Box::new(folder.fold_exp(*expression)),
+                },
+            };
+            Node::Stmt { kind, id, cursor }
+        }
+        Node::Exp {
+            kind,
+            typ,
+            id,
+            cursor,
+            val,
+        } => {
+            let kind = match kind {
+                ExpKind::Op { op, left, right } => ExpKind::Op {
+                    op,
+                    left: Box::new(folder.fold_exp(*left)),
+                    right: right.map(|right| Box::new(folder.fold_exp(*right))),
+                },
+                ExpKind::Call { name, args } => ExpKind::Call {
+                    name,
+                    args: args.into_iter().map(|arg| folder.fold_exp(arg)).collect(),
+                },
+                ExpKind::Index { name, index } => ExpKind::Index {
+                    name,
+                    index: Box::new(folder.fold_exp(*index)),
+                },
+                kind => kind,
+            };
+            Node::Exp {
+                kind,
+                typ,
+                id,
+                cursor,
+                val,
+            }
+        }
+        Node::Decl {
+            kind:
+                DeclKind::Function {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                },
+            id,
+            cursor,
+        } => Node::Decl {
+            kind: DeclKind::Function {
+                name,
+                params,
+                return_type,
+                body: folder.fold_block(body),
+            },
+            id,
+            cursor,
+        },
+        decl @ Node::Decl { .. } => decl,
+    };
+    TreeNode::new(node)
+}
@@ -0,0 +1,103 @@
+use scanner::data::{Token, TokenType};
+
+use crate::{parse, source::offset, symbol_name, Analyzer};
+
+pub const KEYWORDS: [&str; 16] = [
+    "main", "integer", "double", "boolean", "void", "true", "false", "if", "else", "while", "do",
+    "return", "stdin", "stdout", "and", "or",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameError {
+    pub message: String,
+}
+
+impl RenameError {
+    fn new(message: String) -> Self {
+        RenameError { message }
+    }
+}
+
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+// Cambia el nombre de una variable sin tocar el resto del texto. `old` es la llave de la tabla de
+// símbolos: `x` es la primera declaración con ese nombre y `x#1`, `x#2`, etc. las siguientes. Solo
+// se renombran la declaración elegida y los identificadores que el análisis resolvió a ella, así
+// que las variables de otros bloques o funciones que se llaman igual no cambian
+pub fn rename_symbol(
+    source: &str,
+    tokens: Vec<Token>,
+    old: &str,
+    new: &str,
+) -> Result<String, RenameError> {
+    if !is_valid_identifier(new) {
+        return Err(RenameError::new(format!(
+            "{} no es un identificador válido",
+            new
+        )));
+    }
+    if KEYWORDS.contains(&new) {
+        return Err(RenameError::new(format!(
+            "{} es una palabra reservada",
+            new
+        )));
+    }
+
+    let identifiers: Vec<Token> = tokens
+        .iter()
+        .filter(|token| token.token_type == TokenType::ID)
+        .cloned()
+        .collect();
+    let (root, errors) = parse(tokens);
+    let Some(mut root) = root.filter(|_| errors.is_empty()) else {
+        return Err(RenameError::new(
+            "No se puede renombrar en un programa con errores de sintaxis".to_string(),
+        ));
+    };
+    let (_, symbol_table) = Analyzer::new().analyze(&mut root);
+    let Some(symbol) = symbol_table.get(old) else {
+        return Err(RenameError::new(format!("La variable {} no existe", old)));
+    };
+    let name = symbol_name(old);
+    if name == new {
+        return Ok(source.to_string());
+    }
+    // también se evita capturar usos de una variable no declarada con el nombre nuevo
+    if identifiers.iter().any(|token| token.lexemme == new) {
+        return Err(RenameError::new(format!(
+            "Ya existe un identificador con el nombre {}",
+            new
+        )));
+    }
+
+    // los usos incluyen la declaración y tienen el cursor de cada identificador
+    let mut offsets = vec![];
+    for usage in symbol.usages.iter() {
+        match offset(source, &usage.cursor) {
+            Some(start) if source[start..].starts_with(name) => offsets.push(start),
+            _ => {
+                return Err(RenameError::new(format!(
+                    "No se pudo ubicar {} en la posición ({}, {})",
+                    name, usage.cursor.lin, usage.cursor.col
+                )))
+            }
+        }
+    }
+
+    let mut renamed = source.to_string();
+    // `x++` registra dos usos en la misma posición
+    offsets.sort_unstable();
+    offsets.dedup();
+    for offset in offsets.into_iter().rev() {
+        renamed.replace_range(offset..offset + name.len(), new);
+    }
+    Ok(renamed)
+}
@@ -0,0 +1,275 @@
+use std::io::{BufRead, Write};
+
+use scanner::{
+    data::{Token, TokenType},
+    utils::fake_cursor,
+};
+
+use crate::{
+    interpret::{Interpreter, RuntimeError},
+    parse,
+    parse::BOOLEAN,
+    parse_expression,
+    source::tokenize_str,
+    structures::{AnalyzeError, DeclKind, ExpType, Node, NodeValue, ParseError, Program, TreeNode},
+    Analyzer,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplError {
+    Lex(Vec<String>),
+    Parse(Vec<ParseError>),
+    Analyze(Vec<AnalyzeError>),
+    Runtime(RuntimeError),
+}
+
+// Resultado de evaluar una entrada: las expresiones sueltas regresan su tipo y valor
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplOutput {
+    Executed,
+    Value(ExpType, NodeValue),
+}
+
+// Variable o arreglo declarado en la sesión. Las variables tienen un solo valor y los arreglos
+// uno por elemento
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplSymbol {
+    pub name: String,
+    pub typ: ExpType,
+    pub size: Option<usize>,
+    pub values: Vec<NodeValue>,
+}
+
+// Sesión interactiva: las declaraciones y el estado de las variables se conservan entre entradas
+pub struct Repl {
+    declarations: Vec<TreeNode>,
+    history: Vec<TreeNode>,
+    interpreter: Interpreter,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Repl {
+            declarations: vec![],
+            history: vec![],
+            interpreter: Interpreter::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.declarations.clear();
+        self.history.clear();
+        self.interpreter = Interpreter::new();
+    }
+
+    // La entrada está completa cuando todas las llaves abiertas se cerraron y no queda un
+    // comentario `/*` abierto. Un `do` además necesita su `while (...);`
+    pub fn is_complete(input: &str) -> bool {
+        let code = strip_comments(input);
+        let Some(code) = code else {
+            return false;
+        };
+        let open = code.matches('{').count();
+        let close = code.matches('}').count();
+        let trimmed = code.trim();
+        let is_do = trimmed
+            .strip_prefix("do")
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'));
+        open <= close && (!is_do || trimmed.ends_with(';'))
+    }
+
+    // Declaraciones y sentencias aceptadas hasta ahora
+    pub fn program(&self) -> Program {
+        Program::new(self.history.clone())
+    }
+
+    // Variables y arreglos en orden de declaración, con su tipo y valor actual
+    pub fn symbols(&self) -> Vec<ReplSymbol> {
+        self.declarations
+            .iter()
+            .filter_map(|node| match &node.node {
+                Node::Decl {
+                    kind: DeclKind::Var { typ, name },
+                    ..
+                } => Some(ReplSymbol {
+                    name: name.clone(),
+                    typ: typ.clone(),
+                    size: None,
+                    values: self.interpreter.value(name).cloned().into_iter().collect(),
+                }),
+                Node::Decl {
+                    kind: DeclKind::Array { typ, name, size },
+                    ..
+                } => Some(ReplSymbol {
+                    name: name.clone(),
+                    typ: typ.clone(),
+                    size: Some(*size),
+                    values: self
+                        .interpreter
+                        .variables()
+                        .get(name)
+                        .map(|variable| variable.elements.clone())
+                        .unwrap_or_default(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>, ReplError> {
+        let (tokens, errors) = tokenize_str(text);
+        if !errors.is_empty() {
+            return Err(ReplError::Lex(
+                errors
+                    .iter()
+                    .map(|err| format!("({}, {}): {}", err.start.lin, err.start.col, err.message))
+                    .collect(),
+            ));
+        }
+        Ok(tokens)
+    }
+
+    pub fn eval(
+        &mut self,
+        input: &str,
+        stdin: &mut dyn BufRead,
+        stdout: &mut dyn Write,
+    ) -> Result<ReplOutput, ReplError> {
+        let tokens = self.tokenize(input)?;
+        let is_statement = match tokens.first().map(|token| &token.token_type) {
+            Some(
+                TokenType::INTEGER
+                | TokenType::DOUBLE
+                | TokenType::IF
+                | TokenType::WHILE
+                | TokenType::DO
+                | TokenType::STDIN
+                | TokenType::STDOUT,
+            ) => true,
+            Some(TokenType::ID) => match tokens.get(1).map(|token| &token.token_type) {
+                Some(TokenType::ASSIGN | TokenType::INC | TokenType::DEC) => true,
+                // `boolean x;` es una declaración
+                Some(TokenType::ID) => tokens[0].lexemme == BOOLEAN,
+                // `a[i] = x;` es una asignación y `a[i]` una expresión
+                Some(TokenType::LBRACKET) => tokens
+                    .iter()
+                    .any(|token| token.token_type == TokenType::ASSIGN),
+                _ => false,
+            },
+            _ => false,
+        };
+        if is_statement {
+            self.statements(input, stdin, stdout)
+        } else {
+            self.expression(tokens, stdin, stdout)
+        }
+    }
+
+    // Analiza los nodos nuevos junto con las declaraciones previas y regresa los nodos anotados
+    fn analyze(&self, nodes: Vec<TreeNode>) -> Result<Vec<TreeNode>, ReplError> {
+        let previous = self.declarations.len();
+        let mut program = Program::new(self.declarations.iter().cloned().chain(nodes).collect());
+        let (errors, _) = Analyzer::new().analyze(&mut program);
+        if !errors.is_empty() {
+            return Err(ReplError::Analyze(errors));
+        }
+        Ok(program.body.split_off(previous))
+    }
+
+    fn statements(
+        &mut self,
+        input: &str,
+        stdin: &mut dyn BufRead,
+        stdout: &mut dyn Write,
+    ) -> Result<ReplOutput, ReplError> {
+        // se escanea solo la entrada para que las posiciones de los errores sean las suyas; el
+        // `main { ... }` que necesita el parser se agrega como tokens
+        let mut tokens = self.tokenize(input)?;
+        tokens.splice(
+            0..0,
+            [
+                wrapper_token(TokenType::MAIN, "main"),
+                wrapper_token(TokenType::LBRA, "{"),
+            ],
+        );
+        tokens.push(wrapper_token(TokenType::RBRA, "}"));
+        let (root, errors) = parse(tokens);
+        if !errors.is_empty() {
+            return Err(ReplError::Parse(errors));
+        }
+        let body = self.analyze(root.unwrap_or_default().body)?;
+        for node in body.iter() {
+            if let Node::Decl { .. } = node.node {
+                self.declarations.push(node.clone());
+            }
+        }
+        let result = self.interpreter.execute(&body, stdin, stdout);
+        self.history.extend(body);
+        result.map_err(ReplError::Runtime)?;
+        Ok(ReplOutput::Executed)
+    }
+
+    fn expression(
+        &mut self,
+        tokens: Vec<Token>,
+        stdin: &mut dyn BufRead,
+        stdout: &mut dyn Write,
+    ) -> Result<ReplOutput, ReplError> {
+        let (node, errors) = parse_expression(tokens);
+        let Some(node) = node.filter(|_| errors.is_empty()) else {
+            return Err(ReplError::Parse(errors));
+        };
+        let node = self.analyze(vec![node])?.remove(0);
+        let typ = match &node.node {
+            Node::Exp { typ, .. } => typ.clone(),
+            _ => ExpType::Void,
+        };
+        let value = self
+            .interpreter
+            .evaluate(&node, stdin, stdout)
+            .map_err(ReplError::Runtime)?;
+        Ok(ReplOutput::Value(typ, value))
+    }
+}
+
+// Token del `main { ... }` que envuelve las sentencias; no está en la entrada, así que no tiene
+// posición
+fn wrapper_token(token_type: TokenType, lexemme: &str) -> Token {
+    Token {
+        token_type,
+        lexemme: lexemme.to_string(),
+        start: fake_cursor(),
+        end: fake_cursor(),
+    }
+}
+
+// Quita los comentarios de la entrada. Regresa None si un comentario `/*` sigue abierto
+fn strip_comments(input: &str) -> Option<String> {
+    let mut code = String::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => while chars.next_if(|c| *c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = None;
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == Some('*') => break,
+                        Some(c) => previous = Some(c),
+                        None => return None,
+                    }
+                }
+                code.push(' ');
+            }
+            _ => code.push(c),
+        }
+    }
+    Some(code)
+}
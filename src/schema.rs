@@ -0,0 +1,82 @@
+use std::collections::{BTreeMap, HashMap};
+
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::structures::{AnalyzeError, ParseError, Program, SymbolData};
+
+// Versión del formato de `--json`. Cualquier cambio en el esquema necesita subirla y guardar el
+// esquema nuevo en `schema/v{FORMAT_VERSION}.json`
+pub const FORMAT_VERSION: u32 = 5;
+
+// Versión más vieja cuyo árbol se puede leer. La 2 agregó el bloque de cada símbolo, la 3 las
+// constantes booleanas, la 4 las funciones y la 5 los arreglos, así que los árboles anteriores
+// siguen siendo válidos
+const OLDEST_READABLE_VERSION: u32 = 1;
+
+// Documento que escribe `--json`
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct AstDocument {
+    pub format_version: u32,
+    pub program: Program,
+    pub symbols: BTreeMap<String, SymbolData>,
+    pub parse_errors: Vec<ParseError>,
+    pub analyze_errors: Vec<AnalyzeError>,
+}
+
+impl AstDocument {
+    pub fn new(
+        program: Program,
+        symbols: &HashMap<String, SymbolData>,
+        parse_errors: Vec<ParseError>,
+        analyze_errors: Vec<AnalyzeError>,
+    ) -> Self {
+        AstDocument {
+            format_version: FORMAT_VERSION,
+            program,
+            // ordenado para que la salida sea estable
+            symbols: symbols.clone().into_iter().collect(),
+            parse_errors,
+            analyze_errors,
+        }
+    }
+}
+
+// Forma serializada de los tipos del scanner, que no implementan `JsonSchema`
+
+#[derive(JsonSchema)]
+#[schemars(rename = "Cursor")]
+pub struct CursorSchema {
+    pub lin: usize,
+    pub col: usize,
+}
+
+#[derive(JsonSchema)]
+#[schemars(rename = "Token")]
+pub struct TokenSchema {
+    pub token_type: String,
+    pub lexemme: String,
+    pub start: CursorSchema,
+    pub end: CursorSchema,
+}
+
+pub fn json_schema() -> String {
+    let schema = schema_for!(AstDocument);
+    serde_json::to_string_pretty(&schema).expect("el esquema siempre se puede serializar") + "\n"
+}
+
+// Lee la salida de `--json`. También acepta el árbol sin versión de las versiones anteriores
+pub fn program_from_json(json: &str) -> Result<Program, String> {
+    match serde_json::from_str::<AstDocument>(json) {
+        Ok(document)
+            if !(OLDEST_READABLE_VERSION..=FORMAT_VERSION).contains(&document.format_version) =>
+        {
+            Err(format!(
+                "La versión del formato {} no es compatible (se esperaba {})",
+                document.format_version, FORMAT_VERSION
+            ))
+        }
+        Ok(document) => Ok(document.program),
+        Err(e) => serde_json::from_str::<Program>(json).map_err(|_| e.to_string()),
+    }
+}
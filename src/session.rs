@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use scanner::data::{Cursor, Token};
+
+use crate::{
+    codegen::{generate_tac, Instruction},
+    config::Config,
+    parse,
+    source::tokenize_str,
+    structures::{AnalyzeError, ParseError, Program, SymbolData},
+    Analyzer,
+};
+
+// Etapa del compilador que generó un diagnóstico
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Io,
+    Lex,
+    Parse,
+    Analyze,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::Io => "io",
+            Stage::Lex => "lex",
+            Stage::Parse => "parse",
+            Stage::Analyze => "analyze",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    // nombre del archivo, o el nombre virtual de un texto en memoria
+    pub file: String,
+    pub stage: Stage,
+    pub message: String,
+    pub cursor: Option<Cursor>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.cursor {
+            Some(cursor) => write!(
+                f,
+                "{}:{}:{}: [{}] {}",
+                self.file, cursor.lin, cursor.col, self.stage, self.message
+            ),
+            None => write!(f, "{}: [{}] {}", self.file, self.stage, self.message),
+        }
+    }
+}
+
+// Nombre virtual de los textos de `parse_str` y `analyze_str`
+pub const STRING_NAME: &str = "<string>";
+// El código leído de stdin usa el mismo nombre en los diagnósticos y en sus artefactos
+pub const STDIN_NAME: &str = "stdin";
+
+// Parsea un texto en memoria, sin escribir archivos
+pub fn parse_str(source: &str) -> (Option<Program>, Vec<Diagnostic>) {
+    let mut session = Session::from_source(STRING_NAME, source);
+    let program = session.program().cloned();
+    (program, session.diagnostics)
+}
+
+// Parsea y analiza un texto en memoria; regresa el árbol anotado y la tabla de símbolos
+pub fn analyze_str(
+    source: &str,
+) -> (
+    Option<Program>,
+    HashMap<String, SymbolData>,
+    Vec<Diagnostic>,
+) {
+    let mut session = Session::from_source(STRING_NAME, source);
+    session.analyze();
+    let (program, symbols) = session.analyzed.take().flatten().unzip();
+    (program, symbols.unwrap_or_default(), session.diagnostics)
+}
+
+enum Input {
+    Path(PathBuf),
+    Source(String),
+}
+
+// Corre las etapas del compilador sobre un archivo o un texto. Cada etapa se ejecuta la primera
+// vez que se pide su resultado (junto con las anteriores) y se guarda para las siguientes
+pub struct Session {
+    name: String,
+    input: Input,
+    source: Option<String>,
+    tokens: Option<Option<Vec<Token>>>,
+    parsed: Option<Option<Program>>,
+    analyzed: Option<Option<(Program, HashMap<String, SymbolData>)>>,
+    tac: Option<Option<Vec<Instruction>>>,
+    parse_errors: Vec<ParseError>,
+    analyze_errors: Vec<AnalyzeError>,
+    diagnostics: Vec<Diagnostic>,
+    warnings: Vec<Diagnostic>,
+    config: Config,
+}
+
+impl Session {
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Session::new(path.to_string_lossy().to_string(), Input::Path(path))
+    }
+
+    // `name` solo se usa para identificar la sesión en los mensajes
+    pub fn from_source(name: &str, source: &str) -> Self {
+        Session::new(name.to_string(), Input::Source(source.to_string()))
+    }
+
+    // Lee todo stdin; los diagnósticos usan el nombre `stdin`
+    pub fn from_stdin() -> io::Result<Self> {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        Ok(Session::from_source(STDIN_NAME, &source))
+    }
+
+    fn new(name: String, input: Input) -> Self {
+        Session {
+            name,
+            input,
+            source: None,
+            tokens: None,
+            parsed: None,
+            analyzed: None,
+            tac: None,
+            parse_errors: vec![],
+            analyze_errors: vec![],
+            diagnostics: vec![],
+            warnings: vec![],
+            config: Config::default(),
+        }
+    }
+
+    // Usa los niveles de los lints de la configuración en el análisis
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.config = config.clone();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Texto fuente, si se pudo leer
+    pub fn source(&mut self) -> Option<&str> {
+        self.lex();
+        self.source.as_deref()
+    }
+
+    pub fn tokens(&mut self) -> &[Token] {
+        self.lex();
+        self.tokens
+            .as_ref()
+            .and_then(|tokens| tokens.as_deref())
+            .unwrap_or_default()
+    }
+
+    // Árbol tal como sale del parser
+    pub fn program(&mut self) -> Option<&Program> {
+        self.parse();
+        self.parsed.as_ref().and_then(|program| program.as_ref())
+    }
+
+    // Árbol anotado por el análisis semántico
+    pub fn analyzed_program(&mut self) -> Option<&Program> {
+        self.analyze();
+        self.analyzed
+            .as_ref()
+            .and_then(|analyzed| analyzed.as_ref())
+            .map(|(program, _)| program)
+    }
+
+    pub fn symbols(&mut self) -> Option<&HashMap<String, SymbolData>> {
+        self.analyze();
+        self.analyzed
+            .as_ref()
+            .and_then(|analyzed| analyzed.as_ref())
+            .map(|(_, symbols)| symbols)
+    }
+
+    // Código de tres direcciones. Solo se genera si ninguna etapa anterior tuvo errores
+    pub fn tac(&mut self) -> Option<&[Instruction]> {
+        if self.tac.is_none() {
+            self.analyze();
+            let tac = match &self.analyzed {
+                Some(Some((program, _))) if self.diagnostics.is_empty() => {
+                    Some(generate_tac(program))
+                }
+                _ => None,
+            };
+            self.tac = Some(tac);
+        }
+        self.tac.as_ref().and_then(|tac| tac.as_deref())
+    }
+
+    // Errores tal como los da el parser, para quien necesite más que el diagnóstico
+    pub fn parse_errors(&mut self) -> &[ParseError] {
+        self.parse();
+        &self.parse_errors
+    }
+
+    pub fn analyze_errors(&mut self) -> &[AnalyzeError] {
+        self.analyze();
+        &self.analyze_errors
+    }
+
+    // Corre hasta el análisis semántico y regresa todos los diagnósticos
+    pub fn check(&mut self) -> &[Diagnostic] {
+        self.analyze();
+        &self.diagnostics
+    }
+
+    // Diagnósticos de las etapas que se han corrido hasta ahora
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    // Advertencias de los lints; no cuentan como errores
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.diagnostics.is_empty()
+    }
+
+    fn error(&mut self, stage: Stage, message: String, cursor: Option<Cursor>) {
+        self.diagnostics.push(Diagnostic {
+            file: self.name.clone(),
+            stage,
+            message,
+            cursor,
+        });
+    }
+
+    fn lex(&mut self) {
+        if self.tokens.is_some() {
+            return;
+        }
+        let result = match &self.input {
+            Input::Path(path) => fs::read_to_string(path)
+                .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))
+                .map(|source| {
+                    let result = tokenize_str(&source);
+                    self.source = Some(source);
+                    result
+                }),
+            Input::Source(source) => {
+                self.source = Some(source.clone());
+                Ok(tokenize_str(source))
+            }
+        };
+        match result {
+            Ok((tokens, errors)) => {
+                for err in errors {
+                    self.error(Stage::Lex, err.message, Some(err.start));
+                }
+                self.tokens = Some(Some(tokens));
+            }
+            Err(e) => {
+                self.error(Stage::Io, e, None);
+                self.tokens = Some(None);
+            }
+        }
+    }
+
+    fn parse(&mut self) {
+        if self.parsed.is_some() {
+            return;
+        }
+        self.lex();
+        // si el archivo no se pudo leer no hay nada que parsear
+        let Some(tokens) = self.tokens.clone().flatten() else {
+            self.parsed = Some(None);
+            return;
+        };
+        let (root, errors) = parse(tokens);
+        for err in errors.iter() {
+            let cursor = err.current_token.as_ref().map(|token| token.start.clone());
+            self.error(Stage::Parse, err.message.clone(), cursor);
+        }
+        self.parse_errors = errors;
+        self.parsed = Some(root);
+    }
+
+    fn analyze(&mut self) {
+        if self.analyzed.is_some() {
+            return;
+        }
+        self.parse();
+        let analyzed = match self.parsed.clone().flatten() {
+            Some(mut program) => {
+                let (errors, warnings, symbols) = Analyzer::new()
+                    .with_config(&self.config)
+                    .analyze_with_warnings(&mut program);
+                for err in errors.iter() {
+                    self.error(
+                        Stage::Analyze,
+                        err.message.clone(),
+                        Some(err.cursor.clone()),
+                    );
+                }
+                for warning in warnings {
+                    self.warnings.push(Diagnostic {
+                        file: self.name.clone(),
+                        stage: Stage::Analyze,
+                        message: warning.message,
+                        cursor: Some(warning.cursor),
+                    });
+                }
+                self.analyze_errors = errors;
+                Some((program, symbols))
+            }
+            None => None,
+        };
+        self.analyzed = Some(analyzed);
+    }
+}
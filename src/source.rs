@@ -0,0 +1,33 @@
+use std::{fs, io};
+
+use scanner::data::{Cursor, ScanError, Token};
+
+pub fn tokenize_file(path: &str) -> io::Result<(Vec<Token>, Vec<ScanError>)> {
+    Ok(tokenize_str(&fs::read_to_string(path)?))
+}
+
+pub fn tokenize_str(source: &str) -> (Vec<Token>, Vec<ScanError>) {
+    scanner::tokenize_str(source)
+}
+
+// Convierte un cursor del scanner en una posición del texto fuente. El scanner numera líneas y
+// columnas desde 1; las posiciones del texto empiezan en 0
+pub fn position(cursor: &Cursor) -> (usize, usize) {
+    (cursor.lin.saturating_sub(1), cursor.col.saturating_sub(1))
+}
+
+// Posición en bytes dentro del texto, None si el cursor está fuera
+pub fn offset(source: &str, cursor: &Cursor) -> Option<usize> {
+    let (lin, col) = position(cursor);
+    let mut line_start = 0;
+    for _ in 0..lin {
+        line_start += source[line_start..].find('\n')? + 1;
+    }
+    let line = &source[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    match line.char_indices().nth(col) {
+        Some((index, _)) => Some(line_start + index),
+        None if col == line.chars().count() => Some(line_start + line.len()),
+        None => None,
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{interpret::value_to_string, structures::SymbolData};
+
+// Formatos en los que se puede exportar la tabla de símbolos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolFormat {
+    Text,
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl SymbolFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SymbolFormat::Text => "txt",
+            SymbolFormat::Json => "json",
+            SymbolFormat::Csv => "csv",
+            SymbolFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SymbolRow<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    data: &'a SymbolData,
+}
+
+// Variables ordenadas por localidad de memoria y después por posición de declaración, para que
+// la salida no dependa del orden del HashMap
+pub fn sorted_symbols(table: &HashMap<String, SymbolData>) -> Vec<(&String, &SymbolData)> {
+    let mut symbols: Vec<_> = table.iter().collect();
+    symbols.sort_by_key(|(name, data)| {
+        (
+            data.mem_location,
+            data.declaration.lin,
+            data.declaration.col,
+            name.to_string(),
+        )
+    });
+    symbols
+}
+
+pub fn format_symbol_table(table: &HashMap<String, SymbolData>, format: SymbolFormat) -> String {
+    let symbols = sorted_symbols(table);
+    match format {
+        SymbolFormat::Text => to_text(&symbols),
+        SymbolFormat::Json => {
+            let rows: Vec<_> = symbols
+                .iter()
+                .map(|(name, data)| SymbolRow { name, data })
+                .collect();
+            serde_json::to_string_pretty(&rows).expect("la tabla siempre se puede serializar")
+                + "\n"
+        }
+        SymbolFormat::Csv => to_csv(&symbols),
+        SymbolFormat::Markdown => to_markdown(&symbols),
+    }
+}
+
+fn to_text(symbols: &[(&String, &SymbolData)]) -> String {
+    let mut out = String::from("------ TABLA DE SIMBOLOS --------\n");
+    for (name, data) in symbols {
+        out.push_str(&format!(
+            "Variable:  {}  | Position ({},{}) | Type: {:?} | Value: {:?} | Location {} | ",
+            name,
+            data.declaration.lin,
+            data.declaration.col,
+            data.typ,
+            data.value,
+            data.mem_location,
+        ));
+        // los arreglos ocupan `size` localidades desde `mem_location`
+        if let Some(size) = data.size {
+            out.push_str(&format!("Size {} | ", size));
+        }
+        out.push_str(&format!(
+            "Scope {} | Function {} | Usages: ",
+            data.scope,
+            function_name(data)
+        ));
+        for usage in data.usages.iter() {
+            out.push_str(&format!("({}, {}),", usage.cursor.lin, usage.cursor.col));
+        }
+        out.push('\n');
+    }
+    out.push_str("------ TABLA DE SIMBOLOS --------\n");
+    out
+}
+
+// Las variables del main no pertenecen a ninguna función
+fn function_name(data: &SymbolData) -> &str {
+    data.function.as_deref().unwrap_or("main")
+}
+
+const COLUMNS: [&str; 10] = [
+    "name", "type", "value", "location", "size", "scope", "function", "line", "column", "usages",
+];
+
+fn columns(name: &str, data: &SymbolData) -> [String; 10] {
+    [
+        name.to_string(),
+        format!("{:?}", data.typ),
+        data.value.as_ref().map(value_to_string).unwrap_or_default(),
+        data.mem_location.to_string(),
+        data.size.map(|size| size.to_string()).unwrap_or_default(),
+        data.scope.to_string(),
+        function_name(data).to_string(),
+        data.declaration.lin.to_string(),
+        data.declaration.col.to_string(),
+        data.usages
+            .iter()
+            .map(|usage| format!("{}:{}", usage.cursor.lin, usage.cursor.col))
+            .collect::<Vec<_>>()
+            .join(" "),
+    ]
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_csv(symbols: &[(&String, &SymbolData)]) -> String {
+    let mut out = COLUMNS.join(",") + "\n";
+    for (name, data) in symbols {
+        let row: Vec<_> = columns(name, data).iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn to_markdown(symbols: &[(&String, &SymbolData)]) -> String {
+    let mut out = format!("| {} |\n", COLUMNS.join(" | "));
+    out.push_str(&format!("|{}\n", "---|".repeat(COLUMNS.len())));
+    for (name, data) in symbols {
+        let row: Vec<_> = columns(name, data)
+            .iter()
+            .map(|field| field.replace('|', "\\|"))
+            .collect();
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    out
+}
@@ -0,0 +1,41 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+// Compila `source` leyéndolo de stdin con `build -`, en un directorio vacío para los artefactos
+fn build_stdin(name: &str, source: &str) -> Output {
+    let dir = env::temp_dir().join(format!("vanilla-build-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_parser"))
+        .args(["build", "-"])
+        .current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+#[test]
+fn lexer_errors_are_printed_without_verbose() {
+    let output = build_stdin("lex", "main {\n    integer x;\n    x = 1 @ 2;\n}\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(4), "{}", stderr);
+    assert!(
+        stderr.contains("ERROR: stdin:3:11: [lex] Caracter invalido @"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,104 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use parser::config::{Config, LintLevel, CONFIG_FILE};
+
+const PROGRAM: &str = "main {\n    integer x;\n    x = 1;\n    stdout x;\n}\n";
+
+// Directorio vacío para una prueba, con `vanilla.toml` y un programa en `src/`
+fn project(name: &str, config: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("vanilla-config-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join(CONFIG_FILE), config).unwrap();
+    fs::write(dir.join("src/programa.cat"), PROGRAM).unwrap();
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_parser"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn discovery_walks_up_from_nested_directories() {
+    let dir = project("discover", "");
+    let nested = dir.join("src/a/b");
+    fs::create_dir_all(&nested).unwrap();
+    assert_eq!(Config::discover(&nested), Some(dir.join(CONFIG_FILE)));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn paths_are_relative_to_the_config_file() {
+    let dir = project(
+        "load",
+        "inputs = [\"src/*.cat\"]\n[build]\nout_dir = \"salida\"\n[lints]\nshadowing = \"deny\"\n",
+    );
+    let config = Config::load(&dir.join(CONFIG_FILE)).unwrap();
+    assert_eq!(config.out_dir(), Some(dir.join("salida")));
+    assert_eq!(
+        config.input_files().unwrap(),
+        [dir.join("src/programa.cat").to_string_lossy().to_string()]
+    );
+    assert_eq!(config.lint_level("shadowing"), LintLevel::Deny);
+    assert_eq!(config.lint_level("otro"), LintLevel::Warn);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    let dir = project("unknown-key", "[build]\nsimbolos = true\n");
+    assert!(Config::load(&dir.join(CONFIG_FILE)).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_uses_the_config_of_a_parent_directory() {
+    let dir = project("cli-discover", "[build]\nsymbols = true\n");
+    let output = run(&dir.join("src"), &["build", "programa.cat"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("TABLA DE SIMBOLOS"));
+    // sin configuración no hay tabla
+    let output = run(&dir.join("src"), &["--no-config", "build", "programa.cat"]);
+    assert!(!stdout(&output).contains("TABLA DE SIMBOLOS"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_flags_take_precedence_over_the_config() {
+    let dir = project(
+        "cli-precedence",
+        "[build]\nsymbols = true\nsymbols_format = \"csv\"\n",
+    );
+    let output = run(&dir, &["build", "src/programa.cat"]);
+    assert!(stdout(&output).contains("name,type,value"));
+    let output = run(
+        &dir,
+        &["--symbols-format", "md", "build", "src/programa.cat"],
+    );
+    assert!(stdout(&output).contains("| name | type |"));
+    let output = run(&dir, &["--no-symbols", "build", "src/programa.cat"]);
+    assert!(output.status.success());
+    assert!(!stdout(&output).contains("name,type,value"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_rejects_an_unknown_symbols_format() {
+    let dir = project("cli-format", "[build]\nsymbols_format = \"xml\"\n");
+    let output = run(&dir, &["build", "src/programa.cat"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown symbols_format xml"));
+    fs::remove_dir_all(&dir).unwrap();
+}
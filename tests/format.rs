@@ -0,0 +1,116 @@
+use std::fs;
+
+use parser::{format_source, parse, source::tokenize_str};
+
+// Formatea un texto como lo haría `fmt`, None si tiene errores
+fn format_text(source: &str) -> Option<String> {
+    let (tokens, errors) = tokenize_str(source);
+    if !errors.is_empty() {
+        return None;
+    }
+    format_source(source, tokens).ok()
+}
+
+#[test]
+fn format_keeps_comments_before_closing_brace_inside_the_block() {
+    let source = "main {
+    integer x;
+    while (x < 3) {
+        x++;
+        // dentro del while
+    }
+    // después del while
+    // fin del main
+}
+";
+    assert_eq!(format_text(source).unwrap(), source);
+}
+
+#[test]
+fn format_keeps_comments_on_closing_brace_lines() {
+    let source = "main {
+    integer x;
+    if (x > 0) {
+        x = 1;
+    } else { // rama falsa
+        x = 2;
+    } // fin del if
+    do {
+        x--;
+    } while (x > 0); // fin del do
+}
+";
+    assert_eq!(format_text(source).unwrap(), source);
+}
+
+#[test]
+fn format_is_idempotent_for_assets() {
+    let mut formatted_any = false;
+    for entry in fs::read_dir("assets").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("cat") {
+            continue;
+        }
+        // los archivos con errores de sintaxis no se pueden formatear
+        let Some(first) = format_text(&fs::read_to_string(&path).unwrap()) else {
+            continue;
+        };
+        formatted_any = true;
+
+        let second =
+            format_text(&first).unwrap_or_else(|| panic!("{:?} no se pudo volver a parsear", path));
+
+        assert_eq!(first, second, "{:?} no es idempotente", path);
+    }
+    assert!(formatted_any);
+}
+
+#[test]
+fn format_keeps_the_same_tree() {
+    let source = fs::read_to_string("assets/expresiones.cat").unwrap();
+    let (tokens, _) = tokenize_str(&source);
+    let formatted = format_source(&source, tokens.clone()).unwrap();
+    let (new_tokens, _) = tokenize_str(&formatted);
+
+    let (before, _) = parse(tokens);
+    let (after, _) = parse(new_tokens);
+    let mut before_text = String::new();
+    let mut after_text = String::new();
+    before
+        .unwrap()
+        .pre_order_traversal(&mut |n| before_text += &format!("{:?}", kind_of(n)));
+    after
+        .unwrap()
+        .pre_order_traversal(&mut |n| after_text += &format!("{:?}", kind_of(n)));
+    assert_eq!(before_text, after_text);
+}
+
+// Compara solo la forma del árbol, sin ids ni cursores
+fn kind_of(node: &parser::structures::Node) -> String {
+    use parser::structures::{DeclKind, ExpKind, Node, StmtKind};
+    match node {
+        Node::Stmt { kind, .. } => match kind {
+            StmtKind::If { .. } => "If".to_string(),
+            StmtKind::While { .. } => "While".to_string(),
+            StmtKind::Do { .. } => "Do".to_string(),
+            StmtKind::Assign { name, .. } => format!("Assign {}", name),
+            StmtKind::In { name } => format!("In {}", name),
+            StmtKind::Out { .. } => "Out".to_string(),
+            StmtKind::Return { .. } => "Return".to_string(),
+            StmtKind::Call { .. } => "Call".to_string(),
+        },
+        Node::Exp { kind, .. } => match kind {
+            ExpKind::Op { op, .. } => format!("Op {:?}", op),
+            ExpKind::Const { value } => format!("Const {}", value),
+            ExpKind::ConstF { value } => format!("ConstF {}", value),
+            ExpKind::ConstB { value } => format!("ConstB {}", value),
+            ExpKind::Id { name } => format!("Id {}", name),
+            ExpKind::Call { name, .. } => format!("Call {}", name),
+            ExpKind::Index { name, .. } => format!("Index {}", name),
+        },
+        Node::Decl { kind, .. } => match kind {
+            DeclKind::Function { name, .. } => format!("Function {}", name),
+            kind => format!("{:?}", kind),
+        },
+    }
+}
@@ -0,0 +1,28 @@
+use std::fs;
+
+use parser::golden::run_golden;
+
+// Corre los archivos anotados de tests/golden; `cargo run -- test --bless tests/golden`
+// actualiza las anotaciones
+#[test]
+fn golden_files_match_annotations() {
+    let mut failures = vec![];
+    let mut ran_any = false;
+    for entry in fs::read_dir("tests/golden").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("cat") {
+            continue;
+        }
+        ran_any = true;
+        let result = run_golden(&path);
+        if !result.passed() {
+            failures.push(format!(
+                "{}:\n  {}",
+                result.file,
+                result.failures.join("\n  ")
+            ));
+        }
+    }
+    assert!(ran_any, "no se encontró ningún archivo de prueba");
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,98 @@
+use parser::{
+    analyze_str,
+    interpret::{Interpreter, RuntimeError},
+    parse_str,
+    structures::NodeValue,
+};
+
+// Analiza y ejecuta el programa; regresa la salida y el intérprete para revisar las variables
+fn run(source: &str, stdin: &str) -> (Result<(), RuntimeError>, String, Interpreter) {
+    let (program, _, diagnostics) = analyze_str(source);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let mut interpreter = Interpreter::new();
+    let mut stdout = vec![];
+    let result = interpreter.run(&program.unwrap(), &mut stdin.as_bytes(), &mut stdout);
+    (result, String::from_utf8(stdout).unwrap(), interpreter)
+}
+
+#[test]
+fn evaluates_arithmetic_and_control_flow() {
+    let (result, stdout, interpreter) = run(
+        "main {
+    integer i, suma;
+    double f;
+    i = 0;
+    suma = 0;
+    while (i < 5) {
+        i++;
+        suma = suma + i;
+    }
+    f = 0.5 + 0.25;
+    stdout suma;
+    stdout f;
+}",
+        "",
+    );
+    assert_eq!(result, Ok(()));
+    assert_eq!(stdout, "15\n0.75\n");
+    assert_eq!(interpreter.value("i"), Some(&NodeValue::Int(5)));
+    assert_eq!(interpreter.value("f"), Some(&NodeValue::Float(0.75)));
+}
+
+#[test]
+fn reads_stdin_and_calls_functions() {
+    let (result, stdout, _) = run(
+        "integer cuadrado(integer n) {
+    return n * n;
+}
+
+main {
+    integer x;
+    stdin x;
+    stdout cuadrado(x);
+}",
+        "7\n",
+    );
+    assert_eq!(result, Ok(()));
+    assert_eq!(stdout, "49\n");
+}
+
+#[test]
+fn division_by_zero_is_a_runtime_error() {
+    let (result, _, _) = run(
+        "main {
+    integer a, b;
+    stdin a;
+    b = 10 / a;
+}",
+        "0\n",
+    );
+    assert_eq!(result.unwrap_err().message, "División entre cero");
+}
+
+#[test]
+fn undefined_variables_are_a_runtime_error() {
+    // sin analizar, el intérprete es el que detecta la variable faltante
+    let (program, diagnostics) = parse_str("main {\n    stdout y;\n}");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let error = Interpreter::new()
+        .run(&program.unwrap(), &mut "".as_bytes(), &mut vec![])
+        .unwrap_err();
+    assert_eq!(error.message, "La variable y no está declarada");
+    assert!(error.cursor.is_some());
+}
+
+#[test]
+fn endless_loops_stop_at_the_instruction_limit() {
+    let (program, _, diagnostics) =
+        analyze_str("main {\n    integer x;\n    while (true) {\n        x++;\n    }\n}");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let mut interpreter = Interpreter::new().with_limit(1000);
+    let error = interpreter
+        .run(&program.unwrap(), &mut "".as_bytes(), &mut vec![])
+        .unwrap_err();
+    assert_eq!(error.message, "Se excedió el límite de 1000 instrucciones");
+    assert!(error.cursor.is_some());
+    // la declaración y el while cuentan; luego cada vuelta evalúa la condición y ejecuta x++
+    assert_eq!(interpreter.value("x"), Some(&NodeValue::Int(499)));
+}
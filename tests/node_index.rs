@@ -0,0 +1,100 @@
+use parser::{
+    parse_str,
+    structures::{ExpKind, Node, Program, StmtKind},
+    validate::validate_program,
+};
+
+fn parse_text(source: &str) -> Program {
+    let (root, diagnostics) = parse_str(source);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    root.unwrap()
+}
+
+#[test]
+fn index_finds_every_node_by_id() {
+    let program = parse_text("integer doble(integer n) {\n    return n * 2;\n}\nmain {\n    integer x;\n    x = doble(3);\n}\n",
+    );
+    let index = program.node_index().unwrap();
+    let mut ids = vec![];
+    program.pre_order_traversal(&mut |node| ids.push(node.id()));
+    assert_eq!(index.len(), ids.len());
+    for id in ids {
+        assert_eq!(index.get(id).map(|node| node.id()), Some(id));
+    }
+}
+
+#[test]
+fn index_links_parents_and_children() {
+    let program =
+        parse_text("main {\n    integer x;\n    while (x < 3) {\n        x = x + 1;\n    }\n}\n");
+    let index = program.node_index().unwrap();
+    let while_node = &program.body[1];
+    let Node::Stmt {
+        kind: StmtKind::While { condition, body },
+        ..
+    } = &while_node.node
+    else {
+        panic!("se esperaba un while");
+    };
+    let while_id = while_node.node.id();
+    let assign_id = body[0].node.id();
+
+    // las sentencias de primer nivel no tienen padre
+    assert_eq!(index.parent(while_id), None);
+    assert_eq!(
+        index.children(while_id),
+        vec![condition.node.id(), assign_id]
+    );
+    assert_eq!(index.parent(condition.node.id()), Some(while_id));
+    assert_eq!(index.parent(assign_id), Some(while_id));
+
+    // `x + 1` -> asignación -> while
+    let Node::Stmt {
+        kind: StmtKind::Assign { value, .. },
+        ..
+    } = &body[0].node
+    else {
+        panic!("se esperaba una asignación");
+    };
+    let Node::Exp {
+        kind: ExpKind::Op { left, .. },
+        ..
+    } = &value.node
+    else {
+        panic!("se esperaba una operación");
+    };
+    assert_eq!(
+        index.ancestors(left.node.id()),
+        vec![value.node.id(), assign_id, while_id]
+    );
+    assert!(index.children(left.node.id()).is_empty());
+}
+
+#[test]
+fn index_ignores_unknown_ids() {
+    let program = parse_text("main {\n    integer x;\n}\n");
+    let index = program.node_index().unwrap();
+    let missing = parser::structures::NodeId(u32::MAX - 1);
+    assert!(index.get(missing).is_none());
+    assert_eq!(index.parent(missing), None);
+    assert!(index.children(missing).is_empty());
+}
+
+#[test]
+fn index_rejects_repeated_ids_like_validate_program() {
+    // un árbol leído con `--from-ast` puede traer la misma sentencia dos veces
+    let mut program = parse_text("main {\n    integer x;\n    x = 1;\n}\n");
+    program.body.push(program.body[1].clone());
+    let repeated = program.body[1].node.id();
+
+    let errors = program.node_index().unwrap_err();
+    let messages: Vec<_> = errors.iter().map(|error| error.message.clone()).collect();
+    let expected = format!("El id {} está repetido", repeated.0);
+    assert!(messages.contains(&expected), "{:?}", messages);
+    assert!(errors
+        .iter()
+        .all(|error| error.id != repeated || error.message == expected));
+
+    let validation = validate_program(&program).unwrap_err();
+    assert!(validation.iter().any(|error| error.message == expected));
+}
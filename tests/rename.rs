@@ -0,0 +1,90 @@
+use parser::{rename_symbol, source::tokenize_str};
+
+const PROGRAM: &str = "integer doble(integer x) {
+    return x * 2;
+}
+
+main {
+    integer x;
+    x = 1;
+    if (x > 0) {
+        integer x;
+        x = 2;
+        stdout x;
+    }
+    x++;
+    stdout doble(x);
+}
+";
+
+fn rename(source: &str, old: &str, new: &str) -> Result<String, String> {
+    let (tokens, errors) = tokenize_str(source);
+    assert!(errors.is_empty());
+    rename_symbol(source, tokens, old, new).map_err(|e| e.message)
+}
+
+#[test]
+fn rename_only_touches_the_selected_declaration() {
+    // el parámetro se declara primero, así que es `x`; la del main es `x#1` y la del if `x#2`
+    let renamed = rename(PROGRAM, "x#1", "total").unwrap();
+    assert_eq!(
+        renamed,
+        PROGRAM
+            .replace(
+                "    integer x;\n    x = 1;\n    if (x > 0)",
+                "    integer total;\n    total = 1;\n    if (total > 0)"
+            )
+            .replace(
+                "    x++;\n    stdout doble(x);",
+                "    total++;\n    stdout doble(total);"
+            )
+    );
+}
+
+#[test]
+fn rename_a_shadowing_declaration() {
+    let renamed = rename(PROGRAM, "x#2", "y").unwrap();
+    assert_eq!(
+        renamed,
+        PROGRAM.replace(
+            "        integer x;\n        x = 2;\n        stdout x;",
+            "        integer y;\n        y = 2;\n        stdout y;"
+        )
+    );
+}
+
+#[test]
+fn rename_a_parameter() {
+    let renamed = rename(PROGRAM, "x", "n").unwrap();
+    assert_eq!(
+        renamed,
+        PROGRAM.replace(
+            "integer doble(integer x) {\n    return x * 2;",
+            "integer doble(integer n) {\n    return n * 2;"
+        )
+    );
+}
+
+#[test]
+fn rename_rejects_keywords() {
+    for keyword in ["while", "boolean", "return", "true"] {
+        let error = rename(PROGRAM, "x#1", keyword).unwrap_err();
+        assert!(error.contains("palabra reservada"), "{}", error);
+    }
+}
+
+#[test]
+fn rename_rejects_invalid_identifiers() {
+    for name in ["", "1x", "a-b", "_x", "año"] {
+        let error = rename(PROGRAM, "x#1", name).unwrap_err();
+        assert!(error.contains("no es un identificador válido"), "{}", error);
+    }
+}
+
+#[test]
+fn rename_rejects_existing_names_and_unknown_variables() {
+    let error = rename(PROGRAM, "x#1", "doble").unwrap_err();
+    assert!(error.contains("Ya existe"), "{}", error);
+    let error = rename(PROGRAM, "z", "w").unwrap_err();
+    assert!(error.contains("no existe"), "{}", error);
+}
@@ -0,0 +1,121 @@
+use parser::{
+    repl::{Repl, ReplError, ReplOutput, ReplSymbol},
+    structures::{ExpType, NodeValue},
+};
+
+fn eval(repl: &mut Repl, input: &str) -> Result<ReplOutput, ReplError> {
+    repl.eval(input, &mut "".as_bytes(), &mut vec![])
+}
+
+#[test]
+fn expressions_use_the_declared_variables() {
+    let mut repl = Repl::new();
+    assert_eq!(eval(&mut repl, "integer x;"), Ok(ReplOutput::Executed));
+    assert_eq!(eval(&mut repl, "x = 20;"), Ok(ReplOutput::Executed));
+    assert_eq!(
+        eval(&mut repl, "x * 2 + 2"),
+        Ok(ReplOutput::Value(ExpType::Integer, NodeValue::Int(42)))
+    );
+    assert_eq!(eval(&mut repl, "x++;"), Ok(ReplOutput::Executed));
+    assert_eq!(
+        eval(&mut repl, "x > 20"),
+        Ok(ReplOutput::Value(
+            ExpType::Boolean,
+            NodeValue::Boolean(true)
+        ))
+    );
+}
+
+#[test]
+fn statements_write_to_stdout() {
+    let mut repl = Repl::new();
+    let mut stdout = vec![];
+    eval(&mut repl, "double d;").unwrap();
+    repl.eval("d = 1.5; stdout d + 1.25;", &mut "".as_bytes(), &mut stdout)
+        .unwrap();
+    assert_eq!(String::from_utf8(stdout).unwrap(), "2.75\n");
+}
+
+#[test]
+fn undefined_variables_are_rejected() {
+    let mut repl = Repl::new();
+    let Err(ReplError::Analyze(errors)) = eval(&mut repl, "y + 1") else {
+        panic!("se esperaba un error de análisis");
+    };
+    assert!(errors.iter().any(|error| error.message.contains("y")));
+    assert!(matches!(
+        eval(&mut repl, "y = 1;"),
+        Err(ReplError::Analyze(_))
+    ));
+    // el error no deja declaraciones a medias
+    assert!(repl.symbols().is_empty());
+}
+
+#[test]
+fn braces_continue_the_input() {
+    assert!(Repl::is_complete("integer x;"));
+    assert!(!Repl::is_complete("if (x > 0) {"));
+    assert!(!Repl::is_complete("if (x > 0) {\n  x = 1;\n"));
+    assert!(Repl::is_complete("if (x > 0) {\n  x = 1;\n}"));
+    assert!(!Repl::is_complete("do {\n  x++;\n}"));
+    assert!(Repl::is_complete("do {\n  x++;\n} while (x < 3);"));
+}
+
+#[test]
+fn braces_inside_comments_are_ignored() {
+    assert!(Repl::is_complete("x = 1; // {"));
+    assert!(Repl::is_complete("x = 1; /* { { */"));
+    assert!(!Repl::is_complete("if (x > 0) { // }"));
+    assert!(!Repl::is_complete("x = 1; /* sin cerrar }"));
+    assert!(Repl::is_complete("if (x > 0) { /* } */\n}"));
+}
+
+#[test]
+fn symbols_include_arrays() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "integer n;").unwrap();
+    eval(&mut repl, "integer a[3];").unwrap();
+    eval(&mut repl, "a[1] = 5; n = 2;").unwrap();
+    assert_eq!(
+        repl.symbols(),
+        vec![
+            ReplSymbol {
+                name: "n".to_string(),
+                typ: ExpType::Integer,
+                size: None,
+                values: vec![NodeValue::Int(2)],
+            },
+            ReplSymbol {
+                name: "a".to_string(),
+                typ: ExpType::Integer,
+                size: Some(3),
+                values: vec![NodeValue::Int(0), NodeValue::Int(5), NodeValue::Int(0)],
+            },
+        ]
+    );
+}
+
+#[test]
+fn reset_clears_the_session() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "integer x;").unwrap();
+    repl.reset();
+    assert!(repl.symbols().is_empty());
+    assert!(matches!(eval(&mut repl, "x"), Err(ReplError::Analyze(_))));
+}
+
+#[test]
+fn diagnostics_point_into_the_input() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "integer x;").unwrap();
+    let Err(ReplError::Analyze(errors)) = eval(&mut repl, "x = 1;\nx = true;") else {
+        panic!("se esperaba un error de análisis");
+    };
+    assert_eq!((errors[0].cursor.lin, errors[0].cursor.col), (2, 5));
+
+    let Err(ReplError::Parse(errors)) = eval(&mut repl, "x = 1;\n  x = ;") else {
+        panic!("se esperaba un error de sintaxis");
+    };
+    let token = errors[0].current_token.as_ref().unwrap();
+    assert_eq!((token.start.lin, token.start.col), (2, 7));
+}
@@ -0,0 +1,61 @@
+use std::{env, fs, path::PathBuf};
+
+use parser::{
+    parse,
+    schema::{json_schema, program_from_json, AstDocument, FORMAT_VERSION},
+    source::tokenize_str,
+    Analyzer,
+};
+
+// Falla cuando el esquema de `--json` cambia sin subir `FORMAT_VERSION`.
+// Con `VANILLA_WRITE_SCHEMA=1` se escribe el esquema de una versión nueva
+#[test]
+fn schema_matches_format_version() {
+    let path = PathBuf::from("schema").join(format!("v{}.json", FORMAT_VERSION));
+    let schema = json_schema();
+    let Ok(saved) = fs::read_to_string(&path) else {
+        if env::var_os("VANILLA_WRITE_SCHEMA").is_some() {
+            fs::write(&path, schema).unwrap();
+            return;
+        }
+        panic!(
+            "No existe {}; generarlo con VANILLA_WRITE_SCHEMA=1 cargo test --test schema",
+            path.display()
+        );
+    };
+    assert!(
+        saved == schema,
+        "El esquema cambió sin subir FORMAT_VERSION ({}): súbela y genera el archivo nuevo",
+        FORMAT_VERSION
+    );
+}
+
+#[test]
+fn document_round_trip_for_assets() {
+    let mut checked_any = false;
+    for entry in fs::read_dir("assets").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("cat") {
+            continue;
+        }
+        let (tokens, errors) = tokenize_str(&fs::read_to_string(&path).unwrap());
+        if !errors.is_empty() {
+            continue;
+        }
+        let (root, parse_errors) = parse(tokens);
+        let Some(mut root) = root else {
+            continue;
+        };
+        checked_any = true;
+        let (analyze_errors, symbols) = Analyzer::new().analyze(&mut root);
+        let document = AstDocument::new(root.clone(), &symbols, parse_errors, analyze_errors);
+        let json = serde_json::to_string(&document).unwrap();
+        assert_eq!(
+            program_from_json(&json).unwrap(),
+            root,
+            "el árbol de {} no sobrevive a JSON",
+            path.display()
+        );
+    }
+    assert!(checked_any, "no se revisó ningún archivo");
+}
@@ -0,0 +1,101 @@
+use std::{env, fs, path::Path};
+
+use parser::{format_symbol_table, schema::AstDocument, Session, SymbolFormat};
+
+const SNAPSHOT_DIR: &str = "tests/snapshots";
+
+// Compara `actual` con el snapshot guardado. Con `VANILLA_UPDATE_SNAPSHOTS=1` el snapshot se
+// reescribe en lugar de compararse; regresa la descripción de la diferencia si la hay
+fn check_snapshot(name: &str, actual: &str) -> Option<String> {
+    let path = Path::new(SNAPSHOT_DIR).join(name);
+    if env::var_os("VANILLA_UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(SNAPSHOT_DIR).unwrap();
+        fs::write(&path, actual).unwrap();
+        return None;
+    }
+    match fs::read_to_string(&path) {
+        Ok(saved) if saved == actual => None,
+        Ok(_) => Some(format!("{} cambió", path.display())),
+        Err(_) => Some(format!("no existe {}", path.display())),
+    }
+}
+
+// Un archivo que ya no genera árbol no debe conservar sus snapshots anteriores
+fn check_missing(name: &str) -> Option<String> {
+    let path = Path::new(SNAPSHOT_DIR).join(name);
+    if !path.exists() {
+        return None;
+    }
+    if env::var_os("VANILLA_UPDATE_SNAPSHOTS").is_some() {
+        fs::remove_file(&path).unwrap();
+        return None;
+    }
+    Some(format!(
+        "{} sobra: el archivo ya no genera árbol",
+        path.display()
+    ))
+}
+
+#[test]
+fn assets_match_snapshots() {
+    let mut failures = vec![];
+    let mut checked_any = false;
+    for entry in fs::read_dir("assets").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("cat") {
+            continue;
+        }
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let mut session = Session::from_path(&path);
+        checked_any = true;
+        let program = session.analyzed_program().cloned();
+        // los errores y advertencias se guardan para todos los archivos, tengan árbol o no
+        let diagnostics: String = session
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| format!("ERROR: {}\n", diagnostic))
+            .chain(
+                session
+                    .warnings()
+                    .iter()
+                    .map(|warning| format!("WARNING: {}\n", warning)),
+            )
+            .collect();
+        failures.extend(check_snapshot(
+            &format!("{}.diagnostics.txt", name),
+            &diagnostics,
+        ));
+        let tree_snapshots =
+            ["ast.json", "symbols.txt", "tree.txt"].map(|kind| format!("{}.{}", name, kind));
+        let Some(program) = program else {
+            // sin árbol no debe quedar ningún snapshot que dependa de él
+            for snapshot in tree_snapshots.iter() {
+                failures.extend(check_missing(snapshot));
+            }
+            continue;
+        };
+        let symbols = session.symbols().cloned().unwrap_or_default();
+        let document = AstDocument::new(
+            program.clone(),
+            &symbols,
+            session.parse_errors().to_vec(),
+            session.analyze_errors().to_vec(),
+        );
+        // los ids de los nodos son consecutivos, así que el JSON no cambia entre corridas
+        let json = serde_json::to_string_pretty(&document).unwrap() + "\n";
+        let contents = [
+            json,
+            format_symbol_table(&symbols, SymbolFormat::Text),
+            program.tree_string(),
+        ];
+        for (snapshot, actual) in tree_snapshots.iter().zip(contents.iter()) {
+            failures.extend(check_snapshot(snapshot, actual));
+        }
+    }
+    assert!(checked_any, "no se revisó ningún archivo");
+    assert!(
+        failures.is_empty(),
+        "{}\nsi el cambio es esperado, regenerar con VANILLA_UPDATE_SNAPSHOTS=1 cargo test --test snapshots",
+        failures.join("\n")
+    );
+}
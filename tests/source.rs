@@ -0,0 +1,90 @@
+use parser::source::{offset, position, tokenize_file, tokenize_str};
+use scanner::data::{Cursor, TokenType};
+
+#[test]
+fn tokens_carry_type_lexemme_and_position() {
+    let (tokens, errors) = tokenize_str("main {\n  x = 1.5 <= y2; // fin\n}");
+    assert!(errors.is_empty(), "{:?}", errors);
+    let summary: Vec<_> = tokens
+        .iter()
+        .map(|token| (token.token_type.clone(), token.lexemme.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (TokenType::MAIN, "main"),
+            (TokenType::LBRA, "{"),
+            (TokenType::ID, "x"),
+            (TokenType::ASSIGN, "="),
+            (TokenType::FLOAT, "1.5"),
+            (TokenType::LE, "<="),
+            (TokenType::ID, "y2"),
+            (TokenType::SCOL, ";"),
+            (TokenType::RBRA, "}"),
+        ]
+    );
+    assert_eq!(tokens[4].start, Cursor { lin: 2, col: 7 });
+    assert_eq!(tokens[4].end, Cursor { lin: 2, col: 10 });
+}
+
+#[test]
+fn comments_are_skipped() {
+    let (tokens, errors) = tokenize_str("/* integer\n x; */ x++ // y--\n/* sin cerrar");
+    assert!(errors.is_empty(), "{:?}", errors);
+    let types: Vec<_> = tokens
+        .iter()
+        .map(|token| token.token_type.clone())
+        .collect();
+    assert_eq!(types, [TokenType::ID, TokenType::INC]);
+    assert_eq!(tokens[0].start, Cursor { lin: 2, col: 8 });
+}
+
+#[test]
+fn invalid_characters_are_errors() {
+    let (tokens, errors) = tokenize_str("x = 1 @ 2;");
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Caracter invalido @");
+    assert_eq!(errors[0].start, Cursor { lin: 1, col: 7 });
+}
+
+#[test]
+fn files_and_strings_give_the_same_tokens() {
+    let path = "assets/ejemplo.cat";
+    let source = std::fs::read_to_string(path).unwrap();
+    assert_eq!(tokenize_file(path).unwrap(), tokenize_str(&source));
+}
+
+#[test]
+fn brackets_have_their_own_token_types() {
+    let (tokens, errors) = tokenize_str("a[(i)] = 1;");
+    assert!(errors.is_empty(), "{:?}", errors);
+    let summary: Vec<_> = tokens
+        .iter()
+        .take(6)
+        .map(|token| (token.token_type.clone(), token.lexemme.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (TokenType::ID, "a"),
+            (TokenType::LBRACKET, "["),
+            (TokenType::LPAR, "("),
+            (TokenType::ID, "i"),
+            (TokenType::RPAR, ")"),
+            (TokenType::RBRACKET, "]"),
+        ]
+    );
+    assert_eq!(tokens[1].start, Cursor { lin: 1, col: 2 });
+}
+
+#[test]
+fn cursors_count_from_one_and_positions_from_zero() {
+    let source = "/* á */\nmain {\n  x = 1;\n}";
+    let (tokens, _) = tokenize_str(source);
+    let x = &tokens[2];
+    assert_eq!(x.start, Cursor { lin: 3, col: 3 });
+    assert_eq!(position(&x.start), (2, 2));
+    assert_eq!(&source[offset(source, &x.start).unwrap()..][..1], "x");
+    assert_eq!(offset(source, &Cursor { lin: 9, col: 1 }), None);
+}
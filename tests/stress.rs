@@ -0,0 +1,65 @@
+use std::thread;
+
+use parser::{parse, source::tokenize_str, Analyzer};
+
+const STATEMENTS: usize = 100_000;
+
+#[test]
+fn long_program_does_not_overflow_the_stack() {
+    let mut source = String::from("main {\n    integer x;\n    x = 0;\n");
+    for _ in 0..STATEMENTS {
+        source.push_str("    x = x + 1;\n");
+    }
+    source.push_str("    stdout x;\n}\n");
+
+    let (tokens, lex_errors) = tokenize_str(&source);
+    assert!(lex_errors.is_empty());
+
+    let (root, errors) = parse(tokens);
+    assert!(errors.is_empty());
+    let mut root = root.unwrap();
+    assert_eq!(root.body.len(), STATEMENTS + 3);
+
+    let mut visited = 0;
+    root.pre_order_traversal(&mut |_| visited += 1);
+    assert!(visited > STATEMENTS);
+    assert_eq!(root.node_index().unwrap().len(), visited);
+
+    let (errors, symbols) = Analyzer::new().analyze(&mut root);
+    assert!(errors.is_empty());
+    assert_eq!(symbols["x"].usages.len(), 2 * STATEMENTS + 3);
+}
+
+const DEPTH: usize = 50_000;
+
+#[test]
+fn deep_expression_does_not_overflow_the_mutable_traversal() {
+    // `1 + 1 + ... + 1` queda como un árbol cargado a la izquierda con DEPTH niveles
+    let mut source = String::from("main {\n    integer x;\n    x = 1");
+    for _ in 0..DEPTH {
+        source.push_str(" + 1");
+    }
+    source.push_str(";\n}\n");
+
+    let (tokens, lex_errors) = tokenize_str(&source);
+    assert!(lex_errors.is_empty());
+
+    let (root, errors) = parse(tokens);
+    assert!(errors.is_empty());
+    let mut root = root.unwrap();
+
+    let mut order = vec![];
+    root.post_order_traversal(&mut |node| order.push(node.id()));
+    let mut order_mut = vec![];
+    root.post_order_traversal_mut(&mut |node| order_mut.push(node.id()));
+    assert_eq!(order.len(), 2 * DEPTH + 3);
+    assert_eq!(order, order_mut);
+
+    // liberar los `Box` sigue siendo recursivo, así que se hace en un hilo con más pila
+    thread::Builder::new()
+        .stack_size(256 << 20)
+        .spawn(move || drop(root))
+        .unwrap()
+        .join()
+        .unwrap();
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use parser::{
+    format_symbol_table,
+    structures::{ExpType, NodeValue, SymbolData, SymbolReference},
+    symbols::sorted_symbols,
+    SymbolFormat,
+};
+use scanner::data::Cursor;
+
+fn symbol(location: i32, lin: usize, col: usize, typ: ExpType) -> SymbolData {
+    SymbolData {
+        mem_location: location,
+        declaration: Cursor { lin, col },
+        typ,
+        value: None,
+        usages: vec![SymbolReference {
+            cursor: Cursor { lin, col },
+        }],
+        scope: 0,
+        function: None,
+        size: None,
+    }
+}
+
+// Tabla con una variable, un arreglo y un nombre con los caracteres que escapan CSV y Markdown
+fn table() -> HashMap<String, SymbolData> {
+    let mut total = symbol(0, 2, 13, ExpType::Integer);
+    total.value = Some(NodeValue::Int(3));
+    total.usages.push(SymbolReference {
+        cursor: Cursor { lin: 3, col: 5 },
+    });
+    let mut lista = symbol(1, 3, 12, ExpType::Float);
+    lista.size = Some(2);
+    lista.scope = 1;
+    let mut raro = symbol(3, 1, 1, ExpType::Boolean);
+    raro.value = Some(NodeValue::Boolean(true));
+    raro.function = Some("f|g".to_string());
+    HashMap::from([
+        ("raro,\"x\"".to_string(), raro),
+        ("lista".to_string(), lista),
+        ("total".to_string(), total),
+    ])
+}
+
+#[test]
+fn symbols_are_sorted_by_location_then_declaration() {
+    let mut table = table();
+    // misma localidad: decide la posición de la declaración y después el nombre
+    table.insert("b".to_string(), symbol(0, 1, 5, ExpType::Integer));
+    table.insert("a".to_string(), symbol(0, 1, 5, ExpType::Integer));
+    let names: Vec<_> = sorted_symbols(&table)
+        .into_iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(names, ["a", "b", "total", "lista", "raro,\"x\""]);
+}
+
+#[test]
+fn text_format() {
+    assert_eq!(
+        format_symbol_table(&table(), SymbolFormat::Text),
+        "------ TABLA DE SIMBOLOS --------
+Variable:  total  | Position (2,13) | Type: Integer | Value: Some(Int(3)) | Location 0 | Scope 0 | Function main | Usages: (2, 13),(3, 5),
+Variable:  lista  | Position (3,12) | Type: Float | Value: None | Location 1 | Size 2 | Scope 1 | Function main | Usages: (3, 12),
+Variable:  raro,\"x\"  | Position (1,1) | Type: Boolean | Value: Some(Boolean(true)) | Location 3 | Scope 0 | Function f|g | Usages: (1, 1),
+------ TABLA DE SIMBOLOS --------
+"
+    );
+}
+
+#[test]
+fn csv_format_escapes_fields() {
+    assert_eq!(
+        format_symbol_table(&table(), SymbolFormat::Csv),
+        "name,type,value,location,size,scope,function,line,column,usages
+total,Integer,3,0,,0,main,2,13,2:13 3:5
+lista,Float,,1,2,1,main,3,12,3:12
+\"raro,\"\"x\"\"\",Boolean,true,3,,0,f|g,1,1,1:1
+"
+    );
+}
+
+#[test]
+fn markdown_format_escapes_pipes() {
+    assert_eq!(
+        format_symbol_table(&table(), SymbolFormat::Markdown),
+        "| name | type | value | location | size | scope | function | line | column | usages |
+|---|---|---|---|---|---|---|---|---|---|
+| total | Integer | 3 | 0 |  | 0 | main | 2 | 13 | 2:13 3:5 |
+| lista | Float |  | 1 | 2 | 1 | main | 3 | 12 | 3:12 |
+| raro,\"x\" | Boolean | true | 3 |  | 0 | f\\|g | 1 | 1 | 1:1 |
+"
+    );
+}
+
+#[test]
+fn json_format_keeps_the_order_and_fields() {
+    let json = format_symbol_table(&table(), SymbolFormat::Json);
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+    let names: Vec<_> = rows
+        .iter()
+        .map(|row| row["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["total", "lista", "raro,\"x\""]);
+    assert_eq!(rows[0]["mem_location"], 0);
+    assert_eq!(rows[0]["value"], serde_json::json!({ "Int": 3 }));
+    assert_eq!(rows[0]["usages"].as_array().unwrap().len(), 2);
+    assert_eq!(rows[1]["size"], 2);
+    assert_eq!(rows[1]["scope"], 1);
+    assert_eq!(rows[2]["function"], "f|g");
+    // cada fila se puede leer de vuelta como SymbolData
+    let back: SymbolData = serde_json::from_value(rows[1].clone()).unwrap();
+    assert_eq!(back, table()["lista"]);
+}
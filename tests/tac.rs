@@ -0,0 +1,43 @@
+use std::{env, fs};
+
+use parser::{codegen::tac_to_string, Session};
+
+const TAC_DIR: &str = "tests/tac";
+
+// Cada `.cat` de tests/tac tiene junto su código de tres direcciones esperado en un `.tac`. Con
+// `VANILLA_UPDATE_SNAPSHOTS=1` los `.tac` se reescriben en lugar de compararse
+#[test]
+fn tac_matches_expected_files() {
+    let mut failures = vec![];
+    let mut checked_any = false;
+    for entry in fs::read_dir(TAC_DIR).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("cat") {
+            continue;
+        }
+        checked_any = true;
+        let mut session = Session::from_path(&path);
+        let Some(code) = session.tac() else {
+            failures.push(format!("{}: {:?}", path.display(), session.diagnostics()));
+            continue;
+        };
+        let actual = tac_to_string(code);
+        let expected_path = path.with_extension("tac");
+        if env::var_os("VANILLA_UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&expected_path, actual).unwrap();
+            continue;
+        }
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{}:\nse esperaba:\n{}se obtuvo:\n{}",
+                expected_path.display(),
+                expected,
+                actual
+            )),
+            Err(_) => failures.push(format!("no existe {}", expected_path.display())),
+        }
+    }
+    assert!(checked_any, "no se revisó ningún archivo");
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
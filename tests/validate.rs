@@ -0,0 +1,211 @@
+use parser::{
+    parse_str,
+    structures::{DeclKind, ExpKind, ExpType, Node, NodeId, Program, StmtKind, TreeNode},
+    validate::validate_program,
+};
+use scanner::data::TokenType;
+
+const SOURCE: &str = "integer doble(integer n) {
+    return n * 2;
+}
+
+main {
+    integer x;
+    integer a[2];
+    x = 1 + 2;
+    stdout doble(x);
+    doble(x);
+    boolean b;
+    b = !b;
+}
+";
+
+fn program() -> Program {
+    let (program, diagnostics) = parse_str(SOURCE);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let program = program.unwrap();
+    assert_eq!(validate_program(&program), Ok(()));
+    program
+}
+
+// Regresa el único error de validación del programa
+fn error(program: &Program) -> String {
+    let errors = validate_program(program).unwrap_err();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    errors[0].message.clone()
+}
+
+// Expresión asignada en la sentencia `position` del main
+fn assigned(program: &mut Program, position: usize) -> &mut ExpKind {
+    let Node::Stmt {
+        kind: StmtKind::Assign { value, .. },
+        ..
+    } = &mut program.body[position].node
+    else {
+        panic!("se esperaba una asignación");
+    };
+    let Node::Exp { kind, .. } = &mut value.node else {
+        panic!("se esperaba una expresión");
+    };
+    kind
+}
+
+#[test]
+fn repeated_ids_are_rejected() {
+    let mut program = program();
+    let first = program.body[0].node.id();
+    let Node::Decl { id, .. } = &mut program.body[1].node else {
+        panic!("se esperaba una declaración");
+    };
+    *id = first;
+    assert_eq!(error(&program), format!("El id {} está repetido", first.0));
+}
+
+#[test]
+fn nodes_without_cursor_are_rejected() {
+    let mut program = program();
+    let Node::Stmt { cursor, .. } = &mut program.body[3].node else {
+        panic!("se esperaba una sentencia");
+    };
+    *cursor = None;
+    assert_eq!(error(&program), "El nodo no tiene cursor");
+}
+
+#[test]
+fn functions_inside_main_are_rejected() {
+    let mut program = program();
+    let function = program.functions.remove(0);
+    program.body.push(function);
+    assert_eq!(
+        error(&program),
+        "Las funciones solo se pueden declarar antes del main"
+    );
+}
+
+#[test]
+fn expressions_in_a_block_are_rejected() {
+    let mut program = program();
+    program.body.push(TreeNode::new(Node::Exp {
+        kind: ExpKind::Const { value: 1 },
+        typ: ExpType::Integer,
+        id: NodeId(1000),
+        cursor: None,
+        val: None,
+    }));
+    assert_eq!(
+        error(&program),
+        "Se esperaba una sentencia o declaración, pero se encontró una expresión"
+    );
+}
+
+#[test]
+fn statements_where_an_expression_is_expected_are_rejected() {
+    let mut program = program();
+    let cursor = program.body[0].node.cursor().cloned();
+    let Node::Stmt {
+        kind: StmtKind::Out { expression },
+        ..
+    } = &mut program.body[3].node
+    else {
+        panic!("se esperaba un stdout");
+    };
+    **expression = TreeNode::new(Node::Decl {
+        kind: DeclKind::Var {
+            typ: ExpType::Integer,
+            name: "y".to_string(),
+        },
+        id: NodeId(1000),
+        cursor,
+    });
+    assert_eq!(error(&program), "Se esperaba una expresión");
+}
+
+#[test]
+fn binary_operators_need_a_right_side() {
+    let mut program = program();
+    let ExpKind::Op { right, .. } = assigned(&mut program, 2) else {
+        panic!("se esperaba una suma");
+    };
+    *right = None;
+    assert_eq!(error(&program), "Operacion SUM sin lado derecho");
+}
+
+#[test]
+fn negation_has_no_right_side() {
+    let mut program = program();
+    let ExpKind::Op { right, .. } = assigned(&mut program, 6) else {
+        panic!("se esperaba una negación");
+    };
+    *right = Some(Box::new(TreeNode::new(Node::Exp {
+        kind: ExpKind::ConstB { value: true },
+        typ: ExpType::Boolean,
+        id: NodeId(1000),
+        cursor: None,
+        val: None,
+    })));
+    assert_eq!(error(&program), "La negación no lleva lado derecho");
+}
+
+#[test]
+fn unknown_operators_are_rejected() {
+    let mut program = program();
+    let ExpKind::Op { op, .. } = assigned(&mut program, 2) else {
+        panic!("se esperaba una operación");
+    };
+    *op = TokenType::ASSIGN;
+    assert_eq!(error(&program), "ASSIGN no es un operador");
+}
+
+#[test]
+fn empty_arrays_are_rejected() {
+    let mut program = program();
+    let Node::Decl {
+        kind: DeclKind::Array { size, .. },
+        ..
+    } = &mut program.body[1].node
+    else {
+        panic!("se esperaba un arreglo");
+    };
+    *size = 0;
+    assert_eq!(
+        error(&program),
+        "El arreglo a debe tener al menos un elemento"
+    );
+}
+
+#[test]
+fn variables_need_a_value_type() {
+    let mut program = program();
+    let Node::Decl {
+        kind: DeclKind::Var { typ, .. },
+        ..
+    } = &mut program.body[0].node
+    else {
+        panic!("se esperaba una variable");
+    };
+    *typ = ExpType::Void;
+    assert_eq!(
+        error(&program),
+        "La variable x tiene un tipo inválido: Void"
+    );
+}
+
+#[test]
+fn call_statements_need_a_call() {
+    let mut program = program();
+    let Node::Stmt {
+        kind: StmtKind::Call { expression },
+        ..
+    } = &mut program.body[4].node
+    else {
+        panic!("se esperaba una llamada");
+    };
+    let Node::Exp { kind, .. } = &mut expression.node else {
+        panic!("se esperaba una expresión");
+    };
+    *kind = ExpKind::Const { value: 1 };
+    assert_eq!(
+        error(&program),
+        "La sentencia no contiene una llamada a función"
+    );
+}
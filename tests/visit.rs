@@ -0,0 +1,310 @@
+use parser::{
+    parse_str,
+    structures::{ExpKind, ExpType, Node, Program, StmtKind, TreeNode},
+    visit::{
+        walk_program, walk_program_mut, BlockContext, Context, Fold, Visitor, VisitorMut, Walk,
+    },
+};
+
+fn parse(source: &str) -> Program {
+    let (program, diagnostics) = parse_str(source);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    program.unwrap()
+}
+
+const PROGRAM: &str = "main {
+    integer a[2];
+    a[1] = 2;
+    while (a[0] < 3) {
+        stdout a[0];
+    }
+}
+";
+
+// Anota cada llamada como `edge@depth hook`, y puede saltarse los hijos de un tipo de hook
+#[derive(Default)]
+struct Recorder {
+    log: Vec<String>,
+    skip: Option<&'static str>,
+}
+
+impl Recorder {
+    fn hook(&mut self, name: &'static str, ctx: &Context) -> Walk {
+        self.log
+            .push(format!("{}@{} {}", ctx.edge.label(), ctx.depth, name));
+        if self.skip == Some(name) {
+            Walk::SkipChildren
+        } else {
+            Walk::Continue
+        }
+    }
+}
+
+impl Visitor for Recorder {
+    fn exit_node(&mut self, _node: &TreeNode, ctx: &Context) {
+        self.log
+            .push(format!("{}@{} exit", ctx.edge.label(), ctx.depth));
+    }
+    fn enter_block(&mut self, _block: &[TreeNode], ctx: &BlockContext) -> Walk {
+        self.log
+            .push(format!("{}@{} block", ctx.edge.label(), ctx.depth));
+        Walk::Continue
+    }
+    fn exit_block(&mut self, _block: &[TreeNode], ctx: &BlockContext) {
+        self.log
+            .push(format!("{}@{} end", ctx.edge.label(), ctx.depth));
+    }
+    fn visit_array(&mut self, _: &ExpType, _: &str, _: usize, ctx: &Context) -> Walk {
+        self.hook("array", ctx)
+    }
+    fn visit_assign(
+        &mut self,
+        _: &str,
+        index: Option<&TreeNode>,
+        _: &TreeNode,
+        ctx: &Context,
+    ) -> Walk {
+        assert!(index.is_some());
+        self.hook("assign", ctx)
+    }
+    fn visit_while(&mut self, _: &TreeNode, _: &[TreeNode], ctx: &Context) -> Walk {
+        self.hook("while", ctx)
+    }
+    fn visit_out(&mut self, _: &TreeNode, ctx: &Context) -> Walk {
+        self.hook("out", ctx)
+    }
+    fn visit_op(
+        &mut self,
+        _: &scanner::data::TokenType,
+        _: &TreeNode,
+        _: Option<&TreeNode>,
+        _: &ExpType,
+        ctx: &Context,
+    ) -> Walk {
+        self.hook("op", ctx)
+    }
+    fn visit_const(&mut self, value: i32, _: &ExpType, ctx: &Context) -> Walk {
+        let name = ["const 0", "const 1", "const 2", "const 3"][value as usize];
+        self.hook(name, ctx)
+    }
+    fn visit_index(&mut self, _: &str, _: &TreeNode, _: &ExpType, ctx: &Context) -> Walk {
+        self.hook("index", ctx)
+    }
+}
+
+#[test]
+fn visitor_enters_and_exits_in_source_order() {
+    let mut recorder = Recorder::default();
+    walk_program(&mut recorder, &parse(PROGRAM));
+    let expected = [
+        "main@0 block",
+        "main@0 array",
+        "main@0 exit",
+        "main@0 assign",
+        "index@1 const 1",
+        "index@1 exit",
+        "value@1 const 2",
+        "value@1 exit",
+        "main@0 exit",
+        "main@0 while",
+        "condition@1 op",
+        "left@2 index",
+        "index@3 const 0",
+        "index@3 exit",
+        "left@2 exit",
+        "right@2 const 3",
+        "right@2 exit",
+        "condition@1 exit",
+        "body@1 block",
+        "body@1 out",
+        "expression@2 index",
+        "index@3 const 0",
+        "index@3 exit",
+        "expression@2 exit",
+        "body@1 exit",
+        "body@1 end",
+        "main@0 exit",
+        "main@0 end",
+    ];
+    assert_eq!(recorder.log, expected);
+}
+
+#[test]
+fn skip_children_still_exits_the_node() {
+    let mut recorder = Recorder {
+        skip: Some("while"),
+        ..Default::default()
+    };
+    walk_program(&mut recorder, &parse(PROGRAM));
+    let while_at = recorder
+        .log
+        .iter()
+        .position(|entry| entry == "main@0 while")
+        .unwrap();
+    assert_eq!(
+        &recorder.log[while_at..],
+        ["main@0 while", "main@0 exit", "main@0 end"]
+    );
+}
+
+// Salta los bloques de los ciclos desde `enter_block` y cuenta los nodos que sí visita
+struct BlockSkipper {
+    visited: usize,
+    exited_blocks: usize,
+}
+
+impl Visitor for BlockSkipper {
+    fn enter_node(&mut self, _node: &TreeNode, _ctx: &Context) -> Walk {
+        self.visited += 1;
+        Walk::Continue
+    }
+    fn enter_block(&mut self, _block: &[TreeNode], ctx: &BlockContext) -> Walk {
+        if ctx.owner.is_some() {
+            Walk::SkipChildren
+        } else {
+            Walk::Continue
+        }
+    }
+    fn exit_block(&mut self, _block: &[TreeNode], _ctx: &BlockContext) {
+        self.exited_blocks += 1;
+    }
+}
+
+#[test]
+fn skipping_a_block_skips_its_statements() {
+    let mut skipper = BlockSkipper {
+        visited: 0,
+        exited_blocks: 0,
+    };
+    walk_program(&mut skipper, &parse(PROGRAM));
+    // array, asignación (3 nodos) y while con su condición (5 nodos); el stdout no se visita
+    assert_eq!(skipper.visited, 1 + 3 + 5);
+    assert_eq!(skipper.exited_blocks, 2);
+}
+
+// Cambia el índice de las asignaciones a arreglos y el nombre de todas las variables
+struct Renamer;
+
+impl VisitorMut for Renamer {
+    fn visit_assign(
+        &mut self,
+        name: &mut String,
+        index: Option<&mut TreeNode>,
+        _value: &mut TreeNode,
+        _exp_value: &mut Option<parser::structures::NodeValue>,
+        _ctx: &Context,
+    ) -> Walk {
+        name.push('2');
+        if let Some(TreeNode {
+            node:
+                Node::Exp {
+                    kind: ExpKind::Const { value },
+                    ..
+                },
+        }) = index
+        {
+            *value = 0;
+        }
+        Walk::Continue
+    }
+    fn visit_index(
+        &mut self,
+        name: &mut String,
+        _index: &mut TreeNode,
+        _typ: &mut ExpType,
+        _val: &mut Option<parser::structures::NodeValue>,
+        _ctx: &Context,
+    ) -> Walk {
+        name.push('2');
+        Walk::Continue
+    }
+}
+
+#[test]
+fn visitor_mut_changes_the_tree_in_place() {
+    let mut program = parse(PROGRAM);
+    walk_program_mut(&mut Renamer, &mut program);
+    let Node::Stmt {
+        kind: StmtKind::Assign { name, index, .. },
+        ..
+    } = &program.body[1].node
+    else {
+        panic!("se esperaba una asignación");
+    };
+    assert_eq!(name, "a2");
+    assert!(matches!(
+        index.as_deref().map(|index| &index.node),
+        Some(Node::Exp {
+            kind: ExpKind::Const { value: 0 },
+            ..
+        })
+    ));
+    let mut names = vec![];
+    program.pre_order_traversal(&mut |node| {
+        if let Node::Exp {
+            kind: ExpKind::Index { name, .. },
+            ..
+        } = node
+        {
+            names.push(name.clone());
+        }
+    });
+    assert_eq!(names, ["a2", "a2"]);
+}
+
+// Quita los `stdout` y duplica las constantes enteras
+struct Folder;
+
+impl Fold for Folder {
+    fn fold_stmt(&mut self, node: TreeNode) -> Vec<TreeNode> {
+        if let Node::Stmt {
+            kind: StmtKind::Out { .. },
+            ..
+        } = node.node
+        {
+            return vec![];
+        }
+        vec![parser::visit::fold_children(self, node)]
+    }
+
+    fn fold_exp(&mut self, node: TreeNode) -> TreeNode {
+        let mut node = parser::visit::fold_children(self, node);
+        if let Node::Exp {
+            kind: ExpKind::Const { value },
+            ..
+        } = &mut node.node
+        {
+            *value *= 2;
+        }
+        node
+    }
+}
+
+#[test]
+fn fold_rebuilds_removing_and_changing_nodes() {
+    let program = Folder.fold_program(parse(PROGRAM));
+    let mut consts = vec![];
+    let mut outs = 0;
+    program.pre_order_traversal(&mut |node| match node {
+        Node::Exp {
+            kind: ExpKind::Const { value },
+            ..
+        } => consts.push(*value),
+        Node::Stmt {
+            kind: StmtKind::Out { .. },
+            ..
+        } => outs += 1,
+        _ => {}
+    });
+    assert_eq!(outs, 0);
+    // a[1] = 2 y a[0] < 3, ya duplicados
+    assert_eq!(consts, [2, 4, 0, 6]);
+    let Node::Stmt {
+        kind: StmtKind::While { body, .. },
+        ..
+    } = &program.body[2].node
+    else {
+        panic!("se esperaba un while");
+    };
+    assert!(body.is_empty());
+}